@@ -0,0 +1,17 @@
+// replaces the query string in place, quad-url.js pushes a history entry for every parameter
+url_state_register_js_plugin = function (importObject) {
+    importObject.env.url_state_replace_query = function (query_rs) {
+        let query = get_js_object(query_rs);
+        let url = window.location.origin + window.location.pathname;
+        if (query != "") {
+            url += '?' + query;
+        }
+        window.history.replaceState(null, '', url + window.location.hash);
+    }
+}
+
+miniquad_add_plugin({
+    register_plugin: url_state_register_js_plugin,
+    name: "url_state",
+    version: "0.1.0"
+});
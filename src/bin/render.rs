@@ -0,0 +1,370 @@
+use macroquad::math::{dvec2, vec2, Vec2};
+use newton_fractal::{
+    write_png, AspectPolicy, BigFloat, CancelToken, CpuRenderer, Method, Nova, Scene, Shading,
+    TileOptions, Viewport, DEFAULT_CONVERGENCE_RADIUS, DEFAULT_TOLERANCE, MAX_CYCLE_PERIOD,
+};
+use num_complex::Complex;
+use std::{
+    env,
+    fs::File,
+    io::{self, BufWriter, Write},
+    process,
+};
+
+const USAGE: &str = "Usage: render [options] <output.png>
+
+Options:
+    --root <re,im>        add a root, repeat for every root (default: roots of x^3 - 1)
+    --color <r,g,b>       color of the root with the same position, components in [0, 1]
+    --iterations <n>      maximum number of iterations (default: 30)
+    --method <name>       iteration method: newton, halley, schroeder, chebyshev or
+                          householder[:order] (default: newton)
+    --relaxation <re,im>  complex factor scaling every step (default: 1,0)
+    --nova <mode>         add a constant after every step: off, julia or mandelbrot (default: off)
+    --nova-constant <re,im>
+                          constant added by the julia nova variant (default: 0,0)
+    --tolerance <eps>     stop iterating closer than this to a root, or when a step is
+                          smaller, 0 runs every iteration (default: 1e-5)
+    --shading <mode>      darken basins by iterations: flat, linear, log or gamma (default: flat)
+    --gamma <g>           exponent of the gamma shading (default: 1)
+    --smooth              shade with fractional iteration counts instead of bands
+    --radius <r>          orbits ending further than this from every root have no basin
+                          (default: 0.1)
+    --no-basin-color <r,g,b>
+                          color of points without a basin (default: 0,0,0)
+    --cycles <n>          color basins of attracting cycles up to this period, 0 disables
+                          the search (default: 0)
+    --real <min,max>      real range of the view, stretching the pixels to fit both ranges,
+                          replaces the center and scale (default: -1,1 when --imag is given)
+    --imag <min,max>      imaginary range of the view, like --real (default: -1,1 when
+                          --real is given)
+    --center <re,im>      center of a view of square pixels in decimal, keeping every
+                          digit, replaces the ranges (default: 0,0)
+    --scale <s>           half the span of the shorter side of the view (default: 1)
+    --rotation <radians>  turn the view around its center (default: 0)
+    --arbitrary-precision iterate in as many bits as the view needs, for zooms beyond f64
+    --size <width>x<height>
+                          size of the image in pixels (default: 1920x1080)
+    --threads <n>         number of render threads (default: all cores)
+    --scene <file>        start from a JSON scene file, later options are applied on top of it
+    --quiet               do not print progress
+    -h, --help            print this message";
+
+struct Options {
+    roots: Vec<Vec2>,
+    colors: Vec<[f32; 3]>,
+    iterations: u32,
+    method: Method,
+    relaxation: Vec2,
+    nova: Nova,
+    nova_constant: Vec2,
+    tolerance: f32,
+    shading: Shading,
+    gamma: f32,
+    smooth: bool,
+    convergence_radius: f32,
+    no_basin_color: [f32; 3],
+    max_cycle_period: u32,
+    rotation: f32,
+    // only set by --real and --imag, the view has square pixels otherwise
+    real_range: Option<Vec2>,
+    imag_range: Option<Vec2>,
+    center: Option<Complex<BigFloat>>,
+    scale: Option<f64>,
+    aspect: AspectPolicy,
+    arbitrary_precision: bool,
+    size: (u16, u16),
+    tiles: TileOptions,
+    quiet: bool,
+    output: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            roots: Vec::new(),
+            colors: Vec::new(),
+            iterations: 30,
+            method: Method::default(),
+            relaxation: vec2(1.0, 0.0),
+            nova: Nova::default(),
+            nova_constant: vec2(0.0, 0.0),
+            tolerance: DEFAULT_TOLERANCE,
+            shading: Shading::default(),
+            gamma: 1.0,
+            smooth: false,
+            convergence_radius: DEFAULT_CONVERGENCE_RADIUS,
+            no_basin_color: [0.0, 0.0, 0.0],
+            max_cycle_period: 0,
+            rotation: 0.0,
+            real_range: None,
+            imag_range: None,
+            center: None,
+            scale: None,
+            aspect: AspectPolicy::default(),
+            arbitrary_precision: false,
+            size: (1920, 1080),
+            tiles: TileOptions::default(),
+            quiet: false,
+            output: None,
+        }
+    }
+}
+
+fn parse_list<const N: usize>(option: &str, value: &str) -> Result<[f32; N], String> {
+    let values = value
+        .split(',')
+        .map(|value| value.trim().parse::<f32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("{}: invalid number in '{}': {}", option, value, err))?;
+    values
+        .try_into()
+        .map_err(|_| format!("{}: expected {} comma separated values", option, N))
+}
+
+fn parse_number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, String>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|err| format!("{}: invalid value '{}': {}", option, value, err))
+}
+
+// every digit is kept, f64 or a list of f32 would lose the center of deep zooms
+fn parse_center(value: &str) -> Result<Complex<BigFloat>, String> {
+    let (re, im) = value
+        .split_once(',')
+        .ok_or_else(|| format!("--center: expected <re>,<im>, got '{}'", value))?;
+    Ok(Complex::new(
+        parse_number("--center", re.trim())?,
+        parse_number("--center", im.trim())?,
+    ))
+}
+
+fn parse_size(value: &str) -> Result<(u16, u16), String> {
+    let (width, height) = value
+        .split_once('x')
+        .ok_or_else(|| format!("--size: expected <width>x<height>, got '{}'", value))?;
+    let width: u16 = parse_number("--size", width)?;
+    let height: u16 = parse_number("--size", height)?;
+    if width == 0 || height == 0 {
+        return Err("--size: the image can't be empty".to_owned());
+    }
+    Ok((width, height))
+}
+
+fn read_scene(path: &str, options: &mut Options) -> Result<(), String> {
+    let scene = Scene::load(path).map_err(|err| format!("{}: {}", path, err))?;
+    options.roots = scene.get_roots();
+    options.colors = scene.colors.clone();
+    options.iterations = scene.max_iterations;
+    options.method = scene.method;
+    options.relaxation = scene.get_relaxation();
+    options.nova = scene.nova;
+    options.nova_constant = scene.get_nova_constant();
+    options.tolerance = scene.tolerance;
+    options.shading = scene.shading;
+    options.gamma = scene.gamma;
+    options.smooth = scene.smooth;
+    options.convergence_radius = scene.convergence_radius;
+    options.no_basin_color = scene.no_basin_color;
+    options.max_cycle_period = scene.max_cycle_period;
+    options.rotation = scene.rotation;
+    options.real_range = None;
+    options.imag_range = None;
+    options.center = Some(scene.get_precise_center());
+    options.scale = Some(scene.scale);
+    options.aspect = scene.aspect;
+    options.arbitrary_precision = scene.arbitrary_precision;
+    Ok(())
+}
+
+fn parse_args(args: Vec<String>, options: &mut Options) -> Result<(), String> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            println!("{}", USAGE);
+            process::exit(0);
+        }
+        if arg == "--quiet" {
+            options.quiet = true;
+            continue;
+        }
+        if arg == "--smooth" {
+            options.smooth = true;
+            continue;
+        }
+        if arg == "--arbitrary-precision" {
+            options.arbitrary_precision = true;
+            continue;
+        }
+        if !arg.starts_with("--") {
+            if options.output.replace(arg).is_some() {
+                return Err("only one output file can be given".to_owned());
+            }
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("{}: missing value", arg))?;
+        match arg.as_str() {
+            "--root" => options.roots.push(parse_list::<2>(&arg, &value)?.into()),
+            "--color" => options.colors.push(parse_list::<3>(&arg, &value)?),
+            "--iterations" => options.iterations = parse_number(&arg, &value)?,
+            "--method" => options.method = parse_number(&arg, &value)?,
+            "--relaxation" => options.relaxation = parse_list::<2>(&arg, &value)?.into(),
+            "--nova" => options.nova = parse_number(&arg, &value)?,
+            "--nova-constant" => options.nova_constant = parse_list::<2>(&arg, &value)?.into(),
+            "--tolerance" => options.tolerance = parse_number(&arg, &value)?,
+            "--shading" => options.shading = parse_number(&arg, &value)?,
+            "--gamma" => options.gamma = parse_number(&arg, &value)?,
+            "--radius" => options.convergence_radius = parse_number(&arg, &value)?,
+            "--no-basin-color" => options.no_basin_color = parse_list::<3>(&arg, &value)?,
+            "--cycles" => options.max_cycle_period = parse_number(&arg, &value)?,
+            "--real" | "--imag" => {
+                let range = Some(parse_list::<2>(&arg, &value)?.into());
+                if arg == "--real" {
+                    options.real_range = range;
+                } else {
+                    options.imag_range = range;
+                }
+                options.center = None;
+                options.scale = None;
+            }
+            "--center" | "--scale" => {
+                if arg == "--center" {
+                    options.center = Some(parse_center(&value)?);
+                } else {
+                    options.scale = Some(parse_number(&arg, &value)?);
+                }
+                options.real_range = None;
+                options.imag_range = None;
+            }
+            "--rotation" => options.rotation = parse_number(&arg, &value)?,
+            "--size" => options.size = parse_size(&value)?,
+            "--threads" => options.tiles.threads = parse_number(&arg, &value)?,
+            "--scene" => read_scene(&value, options)?,
+            _ => return Err(format!("unknown option {}", arg)),
+        }
+    }
+    Ok(())
+}
+
+// the same bounds Scene::validate puts on scene files
+fn validate(options: &Options) -> Result<(), String> {
+    if !(options.tolerance.is_finite() && options.tolerance >= 0.0) {
+        return Err("--tolerance: expected a non-negative number".to_owned());
+    }
+    if !(options.gamma.is_finite() && options.gamma > 0.0) {
+        return Err("--gamma: expected a positive number".to_owned());
+    }
+    if !(options.convergence_radius.is_finite() && options.convergence_radius > 0.0) {
+        return Err("--radius: expected a positive number".to_owned());
+    }
+    if !options
+        .no_basin_color
+        .iter()
+        .all(|value| (0.0..=1.0).contains(value))
+    {
+        return Err("--no-basin-color: components must be in [0, 1]".to_owned());
+    }
+    if options.max_cycle_period > MAX_CYCLE_PERIOD {
+        return Err(format!(
+            "--cycles: the period can be at most {}",
+            MAX_CYCLE_PERIOD
+        ));
+    }
+    if !options.rotation.is_finite() {
+        return Err("--rotation: expected a finite number".to_owned());
+    }
+    Ok(())
+}
+
+fn run() -> Result<(), String> {
+    let mut options = Options::default();
+    parse_args(env::args().skip(1).collect(), &mut options)?;
+    let output = options
+        .output
+        .take()
+        .ok_or_else(|| format!("missing output file\n\n{}", USAGE))?;
+    validate(&options)?;
+
+    if options.roots.is_empty() && options.colors.is_empty() {
+        options.roots = vec![
+            vec2(1.0, 0.0),
+            vec2(-0.5, 0.866_025_4),
+            vec2(-0.5, -0.866_025_4),
+        ];
+        options.colors = vec![[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    }
+    let default_range = vec2(-1.0, 1.0);
+    let mut renderer = CpuRenderer::new(
+        options.roots,
+        options.colors,
+        options.iterations,
+        options.real_range.unwrap_or(default_range),
+        options.imag_range.unwrap_or(default_range),
+    )
+    .map_err(|err| err.to_string())?;
+    renderer.set_method(options.method);
+    renderer.set_relaxation(Complex::new(options.relaxation.x, options.relaxation.y));
+    renderer.set_nova(
+        options.nova,
+        Complex::new(options.nova_constant.x, options.nova_constant.y),
+    );
+    renderer.set_tolerance(options.tolerance);
+    renderer.set_shading(options.shading, options.gamma);
+    renderer.set_smooth(options.smooth);
+    renderer.set_no_basin(options.convergence_radius, options.no_basin_color);
+    renderer.set_max_cycle_period(options.max_cycle_period);
+    renderer.set_rotation(options.rotation);
+    // explicit ranges are kept as they are, even if they stretch the pixels
+    if options.real_range.is_none() && options.imag_range.is_none() {
+        let scale = options.scale.unwrap_or(1.0);
+        if !(scale.is_finite() && scale > 0.0) {
+            return Err("--scale: expected a positive number".to_owned());
+        }
+        let mut viewport = Viewport::new(
+            dvec2(0.0, 0.0),
+            scale,
+            vec2(options.size.0 as f32, options.size.1 as f32),
+        );
+        if let Some(center) = options.center.take() {
+            viewport.set_precise_center(center);
+        }
+        viewport.set_aspect(options.aspect);
+        viewport.set_rotation(options.rotation);
+        renderer.set_viewport(&viewport);
+    }
+    renderer.set_arbitrary_precision(options.arbitrary_precision);
+
+    let quiet = options.quiet;
+    let image = renderer
+        .render_tiled(
+            options.size.0,
+            options.size.1,
+            options.tiles,
+            |done, total| {
+                if !quiet {
+                    eprint!("\rRendering: {}/{} tiles", done, total);
+                    io::stderr().flush().ok();
+                }
+            },
+            &CancelToken::new(),
+        )
+        .expect("the render is never cancelled");
+    if !quiet {
+        eprintln!();
+    }
+
+    let file = File::create(&output).map_err(|err| format!("{}: {}", output, err))?;
+    write_png(&renderer, &image, BufWriter::new(file)).map_err(|err| format!("{}: {}", output, err))
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
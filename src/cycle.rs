@@ -0,0 +1,36 @@
+use num_complex::Complex;
+
+// longest period searched for
+pub const MAX_CYCLE_PERIOD: u32 = 8;
+// every cycle point takes two uniform vectors in the shader, further cycles are dropped
+pub const MAX_CYCLE_POINTS: usize = 16;
+
+// an attracting periodic orbit of the iteration which doesn't go through a root
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cycle {
+    // starting with the point of smallest real part
+    pub points: Vec<Complex<f32>>,
+    // derivative of the period-th iterate along the cycle, less than 1 in magnitude
+    pub multiplier: Complex<f32>,
+}
+
+impl Cycle {
+    pub fn period(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn contains(&self, z: Complex<f32>, radius: f32) -> bool {
+        self.points.iter().any(|point| (z - point).norm() <= radius)
+    }
+}
+
+// hues spread by the golden ratio so neighbouring indices stay distinct
+pub fn cycle_color(index: usize) -> [f32; 3] {
+    let hue = (0.1 + index as f32 * 0.618_034).fract() * 6.0;
+    let (saturation, value) = (0.6, 0.9);
+    let channel = |offset: f32| {
+        let k = (offset + hue) % 6.0;
+        value - value * saturation * (k.min(4.0 - k)).clamp(0.0, 1.0)
+    };
+    [channel(5.0), channel(3.0), channel(1.0)]
+}
@@ -0,0 +1,71 @@
+use macroquad::miniquad::ShaderType;
+use std::fmt;
+
+use crate::MAX_ROOTS;
+
+#[derive(Clone, Debug)]
+pub enum FractalError {
+    NoRoots,
+    MismatchedColors {
+        roots: usize,
+        colors: usize,
+    },
+    TooManyRoots {
+        roots: usize,
+        max: usize,
+    },
+    ShaderCompilation {
+        shader_type: ShaderType,
+        source: String,
+        log: String,
+    },
+    ShaderLink(String),
+    Shader(String),
+}
+
+impl fmt::Display for FractalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FractalError::NoRoots => write!(f, "a fractal needs at least one root"),
+            FractalError::MismatchedColors { roots, colors } => write!(
+                f,
+                "{} roots but {} colors, every root needs exactly one color",
+                roots, colors
+            ),
+            FractalError::TooManyRoots { roots, max } => {
+                write!(f, "{} roots but the shader supports at most {}", roots, max)
+            }
+            FractalError::ShaderCompilation {
+                shader_type, log, ..
+            } => write!(
+                f,
+                "failed to compile the {:?} shader:\n{}",
+                shader_type, log
+            ),
+            FractalError::ShaderLink(log) => write!(f, "failed to link the shader:\n{}", log),
+            FractalError::Shader(err) => write!(f, "shader error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for FractalError {}
+
+pub(crate) fn check_roots(roots: usize, colors: usize) -> Result<(), FractalError> {
+    if roots == 0 {
+        return Err(FractalError::NoRoots);
+    }
+    if roots != colors {
+        return Err(FractalError::MismatchedColors { roots, colors });
+    }
+    Ok(())
+}
+
+pub(crate) fn check_degree(roots: usize) -> Result<(), FractalError> {
+    if roots > MAX_ROOTS {
+        return Err(FractalError::TooManyRoots {
+            roots,
+            max: MAX_ROOTS,
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,290 @@
+use macroquad::{
+    math::{vec2, Vec2},
+    texture::Image,
+};
+use num_complex::Complex;
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{BigFloat, CpuRenderer, Method, Nova, Shading};
+
+const SOFTWARE: &str = "newton_fractal";
+
+// groups are separated by ';' and values inside a group by ' ', e.g. "1 0;-0.5 0.8660254"
+pub(crate) fn format_groups<'a>(groups: impl Iterator<Item = &'a [f32]>) -> String {
+    groups
+        .map(|group| {
+            group
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+pub(crate) fn parse_groups(text: &str, group_len: usize) -> Option<Vec<Vec<f32>>> {
+    if text.is_empty() {
+        return Some(Vec::new());
+    }
+    text.split(';')
+        .map(|group| {
+            let values = group
+                .split_whitespace()
+                .map(|value| value.parse::<f32>().ok())
+                .collect::<Option<Vec<_>>>()?;
+            (values.len() == group_len).then_some(values)
+        })
+        .collect()
+}
+
+pub(crate) fn format_vec2(value: Vec2) -> String {
+    format!("{} {}", value.x, value.y)
+}
+
+pub(crate) fn parse_vec2(text: &str) -> Option<Vec2> {
+    match parse_groups(text, 2)?.as_slice() {
+        [range] => Some(vec2(range[0], range[1])),
+        _ => None,
+    }
+}
+
+// f64 keeps the center of deep zooms
+pub(crate) fn format_complex(value: Complex<f64>) -> String {
+    format!("{} {}", value.re, value.im)
+}
+
+pub(crate) fn parse_complex(text: &str) -> Option<Complex<f64>> {
+    let mut values = text.split_whitespace().map(|value| value.parse().ok());
+    match (values.next(), values.next(), values.next()) {
+        (Some(re), Some(im), None) => Some(Complex::new(re?, im?)),
+        _ => None,
+    }
+}
+
+// decimal with every digit, deep zooms go beyond f64
+pub(crate) fn format_big_complex(value: &Complex<BigFloat>) -> String {
+    format!("{} {}", value.re, value.im)
+}
+
+pub(crate) fn parse_big_complex(text: &str) -> Option<Complex<BigFloat>> {
+    let mut values = text.split_whitespace().map(|value| value.parse().ok());
+    match (values.next(), values.next(), values.next()) {
+        (Some(re), Some(im), None) => Some(Complex::new(re?, im?)),
+        _ => None,
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn parameters(renderer: &CpuRenderer) -> Vec<(&'static str, String)> {
+    let roots: Vec<[f32; 2]> = renderer
+        .get_roots()
+        .iter()
+        .map(|root| [root.re, root.im])
+        .collect();
+    let relaxation = renderer.get_relaxation();
+    let nova_constant = renderer.get_nova_constant();
+    vec![
+        ("Software", SOFTWARE.to_owned()),
+        ("Roots", format_groups(roots.iter().map(|root| &root[..]))),
+        (
+            "Colors",
+            format_groups(renderer.get_colors().iter().map(|color| &color[..])),
+        ),
+        ("Iterations", renderer.get_max_iterations().to_string()),
+        ("RealRange", format_vec2(renderer.get_real_range())),
+        ("ImagRange", format_vec2(renderer.get_imag_range())),
+        ("Center", format_big_complex(renderer.get_precise_center())),
+        ("Extent", format_complex(renderer.get_extent())),
+        ("Method", renderer.get_method().to_string()),
+        (
+            "Relaxation",
+            format_vec2(vec2(relaxation.re, relaxation.im)),
+        ),
+        ("Nova", renderer.get_nova().to_string()),
+        (
+            "NovaConstant",
+            format_vec2(vec2(nova_constant.re, nova_constant.im)),
+        ),
+        ("Tolerance", renderer.get_tolerance().to_string()),
+        ("Shading", renderer.get_shading().to_string()),
+        ("Gamma", renderer.get_gamma().to_string()),
+        ("Smooth", renderer.get_smooth().to_string()),
+        (
+            "ConvergenceRadius",
+            renderer.get_convergence_radius().to_string(),
+        ),
+        (
+            "NoBasinColor",
+            format_groups([&renderer.get_no_basin_color()[..]].into_iter()),
+        ),
+        (
+            "MaxCyclePeriod",
+            renderer.get_max_cycle_period().to_string(),
+        ),
+        ("Rotation", renderer.get_rotation().to_string()),
+        (
+            "ArbitraryPrecision",
+            renderer.get_arbitrary_precision().to_string(),
+        ),
+    ]
+}
+
+pub fn write_png<W: Write>(renderer: &CpuRenderer, image: &Image, writer: W) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(writer, image.width as u32, image.height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    for (keyword, text) in parameters(renderer) {
+        encoder.add_text_chunk(keyword.to_owned(), text)?;
+    }
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&image.bytes)?;
+    writer.finish()?;
+    Ok(())
+}
+
+pub fn export_png<P: AsRef<Path>>(
+    renderer: &CpuRenderer,
+    path: P,
+    width: u16,
+    height: u16,
+) -> io::Result<()> {
+    let image = renderer.render(width, height);
+    write_png(renderer, &image, BufWriter::new(File::create(path)?))
+}
+
+// rebuilds the renderer from the parameters embedded by `write_png`
+pub fn read_png_parameters<P: AsRef<Path>>(path: P) -> io::Result<CpuRenderer> {
+    let decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+    let reader = decoder.read_info()?;
+    let text = &reader.info().uncompressed_latin1_text;
+    let get = |keyword: &str| {
+        text.iter()
+            .find(|chunk| chunk.keyword == keyword)
+            .map(|chunk| chunk.text.as_str())
+            .ok_or_else(|| invalid_data(&format!("missing {} text chunk", keyword)))
+    };
+
+    if get("Software")? != SOFTWARE {
+        return Err(invalid_data("image was not exported by newton_fractal"));
+    }
+    let roots = parse_groups(get("Roots")?, 2)
+        .ok_or_else(|| invalid_data("malformed roots"))?
+        .iter()
+        .map(|root| vec2(root[0], root[1]))
+        .collect();
+    let colors = parse_groups(get("Colors")?, 3)
+        .ok_or_else(|| invalid_data("malformed colors"))?
+        .iter()
+        .map(|color| [color[0], color[1], color[2]])
+        .collect();
+    let max_iterations = get("Iterations")?
+        .parse()
+        .map_err(|_| invalid_data("malformed iterations"))?;
+    let real_range =
+        parse_vec2(get("RealRange")?).ok_or_else(|| invalid_data("malformed real range"))?;
+    let imag_range =
+        parse_vec2(get("ImagRange")?).ok_or_else(|| invalid_data("malformed imaginary range"))?;
+    // images exported before these options existed were rendered with the defaults
+    let method = match get("Method") {
+        Ok(method) => method.parse().map_err(|err: String| invalid_data(&err))?,
+        Err(_) => Method::default(),
+    };
+    let relaxation = match get("Relaxation") {
+        Ok(relaxation) => {
+            parse_vec2(relaxation).ok_or_else(|| invalid_data("malformed relaxation"))?
+        }
+        Err(_) => vec2(1.0, 0.0),
+    };
+    let nova = match get("Nova") {
+        Ok(nova) => nova.parse().map_err(|err: String| invalid_data(&err))?,
+        Err(_) => Nova::default(),
+    };
+    let nova_constant = match get("NovaConstant") {
+        Ok(constant) => {
+            parse_vec2(constant).ok_or_else(|| invalid_data("malformed nova constant"))?
+        }
+        Err(_) => vec2(0.0, 0.0),
+    };
+    // older images always ran every iteration, which a tolerance of 0 reproduces
+    let tolerance = match get("Tolerance") {
+        Ok(tolerance) => tolerance
+            .parse()
+            .map_err(|_| invalid_data("malformed tolerance"))?,
+        Err(_) => 0.0,
+    };
+    let shading = match get("Shading") {
+        Ok(shading) => shading.parse().map_err(|err: String| invalid_data(&err))?,
+        Err(_) => Shading::default(),
+    };
+    let gamma = match get("Gamma") {
+        Ok(gamma) => gamma.parse().map_err(|_| invalid_data("malformed gamma"))?,
+        Err(_) => 1.0,
+    };
+    let smooth = match get("Smooth") {
+        Ok(smooth) => smooth
+            .parse()
+            .map_err(|_| invalid_data("malformed smooth"))?,
+        Err(_) => false,
+    };
+    // older images put every pixel in a basin
+    let convergence_radius = match get("ConvergenceRadius") {
+        Ok(radius) => radius
+            .parse()
+            .map_err(|_| invalid_data("malformed convergence radius"))?,
+        Err(_) => f32::INFINITY,
+    };
+    let no_basin_color = match get("NoBasinColor") {
+        Ok(color) => match parse_groups(color, 3).as_deref() {
+            Some([color]) => [color[0], color[1], color[2]],
+            _ => return Err(invalid_data("malformed no basin color")),
+        },
+        Err(_) => [0.0, 0.0, 0.0],
+    };
+    let max_cycle_period = match get("MaxCyclePeriod") {
+        Ok(period) => period
+            .parse()
+            .map_err(|_| invalid_data("malformed cycle period"))?,
+        Err(_) => 0,
+    };
+    let rotation = match get("Rotation") {
+        Ok(rotation) => rotation
+            .parse()
+            .map_err(|_| invalid_data("malformed rotation"))?,
+        Err(_) => 0.0,
+    };
+    let arbitrary_precision = match get("ArbitraryPrecision") {
+        Ok(arbitrary_precision) => arbitrary_precision
+            .parse()
+            .map_err(|_| invalid_data("malformed arbitrary precision"))?,
+        Err(_) => false,
+    };
+
+    let mut renderer = CpuRenderer::new(roots, colors, max_iterations, real_range, imag_range)
+        .map_err(|err| invalid_data(&err.to_string()))?;
+    renderer.set_method(method);
+    renderer.set_relaxation(Complex::new(relaxation.x, relaxation.y));
+    renderer.set_nova(nova, Complex::new(nova_constant.x, nova_constant.y));
+    renderer.set_tolerance(tolerance);
+    renderer.set_shading(shading, gamma);
+    renderer.set_smooth(smooth);
+    renderer.set_no_basin(convergence_radius, no_basin_color);
+    renderer.set_max_cycle_period(max_cycle_period);
+    renderer.set_rotation(rotation);
+    // the ranges are rounded to f32, which blurs deep zooms
+    if let (Ok(center), Ok(extent)) = (get("Center"), get("Extent")) {
+        renderer.set_precise_view(
+            parse_big_complex(center).ok_or_else(|| invalid_data("malformed center"))?,
+            parse_complex(extent).ok_or_else(|| invalid_data("malformed extent"))?,
+        );
+    }
+    renderer.set_arbitrary_precision(arbitrary_precision);
+    Ok(renderer)
+}
@@ -1,176 +1,481 @@
 use macroquad::{
     material::{load_material, Material, MaterialParams},
     math::{vec2, Vec2},
-    miniquad::{error, ShaderError, UniformType},
+    miniquad::{ShaderError, ShaderType, UniformType},
 };
 use num_complex::Complex;
 
+use crate::{
+    cycle_color, Cycle, FractalError, IterationMethod, Method, Nova, Shading, Viewport,
+    MAX_CYCLE_POINTS, NOVA_BAILOUT,
+};
+
+// roots, colors and polynomial coefficients take one uniform vector each, 32 roots and
+// the cycle points stay well below the 224 fragment uniform vectors guaranteed by WebGL 2
+pub const MAX_ROOTS: usize = 32;
+
+// every option that changes the generated GLSL
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ShaderKey {
+    pub max_roots: usize,
+    pub method: Method,
+    pub nova: Nova,
+    pub shading: Shading,
+    pub smooth: bool,
+    // iterate in double-float, for zooms past what f32 can resolve
+    pub deep: bool,
+}
+
+impl Default for ShaderKey {
+    fn default() -> Self {
+        Self {
+            max_roots: MAX_ROOTS,
+            method: Method::default(),
+            nova: Nova::default(),
+            shading: Shading::default(),
+            smooth: false,
+            deep: false,
+        }
+    }
+}
+
+// owns a GPU pipeline, which is deleted by the shader cache holding it
 pub struct FractalShader {
     shader: Material,
+    key: ShaderKey,
 }
 
 const VERTEX: &str = include_str!("shaders/vertex.vert");
 
 const FRAGMENT_HEADER: &str = "#version 300 es
 
-#define cx_mul(a, b) vec2(a.x*b.x-a.y*b.y, a.x*b.y+a.y*b.x)
-#define cx_div(a, b) vec2(((a.x*b.x+a.y*b.y)/(b.x*b.x+b.y*b.y)),((a.y*b.x-a.x*b.y)/(b.x*b.x+b.y*b.y)))
-
 precision highp float;
 in vec2 complex;
+// the pixel relative to the view center
+in vec2 offset;
 out vec4 fragColor;
 
+uniform vec2 center;
+// what the view center lost when rounded to f32
+uniform vec2 centerLow;
+
 uniform int maxIterations;
+uniform int rootCount;
+uniform vec2 relaxation;
+uniform vec2 novaConstant;
+uniform float tolerance;
+uniform float gamma;
+uniform float convergenceRadius;
+uniform vec3 noBasinColor;
+uniform int cyclePointCount;
+";
+
+// the iteration works on CX values through these, the rest of the shader stays in vec2
+const COMPLEX_SINGLE: &str = "
+#define CX vec2
+#define cx_from(a) (a)
+#define cx_to_vec2(a) (a)
+#define cx_add(a, b) ((a) + (b))
+#define cx_sub(a, b) ((a) - (b))
+#define cx_neg(a) (-(a))
+#define cx_scale(a, s) ((s) * (a))
+#define cx_mul(a, b) vec2((a).x*(b).x-(a).y*(b).y, (a).x*(b).y+(a).y*(b).x)
+#define cx_div(a, b) vec2((((a).x*(b).x+(a).y*(b).y)/((b).x*(b).x+(b).y*(b).y)),(((a).y*(b).x-(a).x*(b).y)/((b).x*(b).x+(b).y*(b).y)))
+";
+
+// a double-float is the unevaluated sum x + y of two floats, about 46 bits of mantissa as
+// long as the compiler keeps the float operations as written, complex ones are (re, im)
+const COMPLEX_DOUBLE: &str = "
+vec2 ff_quick_two_sum(float a, float b) {
+	float s = a + b;
+	return vec2(s, b - (s - a));
+}
+
+vec2 ff_two_sum(float a, float b) {
+	float s = a + b;
+	float v = s - a;
+	return vec2(s, (a - (s - v)) + (b - v));
+}
+
+// halves of the 24 bit mantissa, their products are exact
+vec2 ff_split(float a) {
+	float t = 4097.0 * a;
+	float high = t - (t - a);
+	return vec2(high, a - high);
+}
+
+vec2 ff_two_prod(float a, float b) {
+	float p = a * b;
+	vec2 x = ff_split(a);
+	vec2 y = ff_split(b);
+	return vec2(p, ((x.x * y.x - p) + x.x * y.y + x.y * y.x) + x.y * y.y);
+}
+
+vec2 ff_add(vec2 a, vec2 b) {
+	vec2 s = ff_two_sum(a.x, b.x);
+	vec2 t = ff_two_sum(a.y, b.y);
+	s = ff_quick_two_sum(s.x, s.y + t.x);
+	return ff_quick_two_sum(s.x, s.y + t.y);
+}
+
+vec2 ff_mul(vec2 a, vec2 b) {
+	vec2 p = ff_two_prod(a.x, b.x);
+	return ff_quick_two_sum(p.x, p.y + (a.x * b.y + a.y * b.x));
+}
+
+vec2 ff_div(vec2 a, vec2 b) {
+	float q = a.x / b.x;
+	vec2 r = ff_add(a, -ff_mul(b, vec2(q, 0.0)));
+	return ff_quick_two_sum(q, r.x / b.x);
+}
+
+#define CX vec4
 
-vec2 cx_pow(vec2 a, float n) {
-    float angle = atan(a.y, a.x);
-    float r = length(a);
-    float real = pow(r, n) * cos(n*angle);
-    float im = pow(r, n) * sin(n*angle);
-    return vec2(real, im);
+CX cx_from(vec2 a) {
+	return vec4(a.x, 0.0, a.y, 0.0);
+}
+
+vec2 cx_to_vec2(CX a) {
+	return vec2(a.x + a.y, a.z + a.w);
+}
+
+CX cx_add(CX a, CX b) {
+	return vec4(ff_add(a.xy, b.xy), ff_add(a.zw, b.zw));
+}
+
+CX cx_sub(CX a, CX b) {
+	return cx_add(a, -b);
+}
+
+CX cx_neg(CX a) {
+	return -a;
+}
+
+CX cx_scale(CX a, float s) {
+	return vec4(ff_mul(a.xy, vec2(s, 0.0)), ff_mul(a.zw, vec2(s, 0.0)));
+}
+
+CX cx_mul(CX a, CX b) {
+	return vec4(
+		ff_add(ff_mul(a.xy, b.xy), -ff_mul(a.zw, b.zw)),
+		ff_add(ff_mul(a.xy, b.zw), ff_mul(a.zw, b.xy))
+	);
+}
+
+CX cx_div(CX a, CX b) {
+	vec2 norm = ff_add(ff_mul(b.xy, b.xy), ff_mul(b.zw, b.zw));
+	return vec4(
+		ff_div(ff_add(ff_mul(a.xy, b.xy), ff_mul(a.zw, b.zw)), norm),
+		ff_div(ff_add(ff_mul(a.zw, b.xy), -ff_mul(a.xy, b.zw)), norm)
+	);
 }
 ";
 
-const FRAGMENT_FOOTER: &str = "
-void newton(inout vec2 z) {
-	z = z - cx_div(evaluate_polynomial(z), evaluate_derivative(z));
+const FRAGMENT_FUNCTIONS: &str = "
+// the last closest root wins, like the CPU renderer
+int closestRoot(vec2 z) {
+	int closest = rootCount - 1;
+	float closestDist = 3.4e38;
+	for (int i = 0; i < rootCount; i++) {
+		float dist = length(z - roots[i]);
+		if (dist <= closestDist) {
+			closestDist = dist;
+			closest = i;
+		}
+	}
+	return closest;
 }
 
-void iterate(inout vec2 z) {
-	for (int i = 0; i < maxIterations; i++) {
-		newton(z);
+CX evaluate_polynomial(CX z) {
+	CX result = cx_from(vec2(1.0, 0.0));
+	for (int i = 0; i < rootCount; i++) {
+		result = cx_mul(result, cx_sub(z, cx_from(roots[i])));
 	}
+	return result;
 }
 
-void main() {
-	vec2 z = complex;
-	iterate(z);
-	closestRoot(z, fragColor);
+// d[k] is the k-th derivative at z, all of them computed in one Horner pass over the coefficients
+void evaluate_derivatives(CX z, out CX d[DERIVATIVES + 1]) {
+	CX horner[DERIVATIVES + 1];
+	horner[0] = cx_from(coeffs[0]);
+	for (int k = 1; k <= DERIVATIVES; k++) {
+		horner[k] = cx_from(vec2(0.0));
+	}
+	for (int i = 1; i <= rootCount; i++) {
+		for (int k = DERIVATIVES; k > 0; k--) {
+			horner[k] = cx_add(cx_mul(horner[k], z), horner[k - 1]);
+		}
+		horner[0] = cx_add(cx_mul(horner[0], z), cx_from(coeffs[i]));
+	}
+	d[0] = evaluate_polynomial(z);
+	float factorial = 1.0;
+	for (int k = 1; k <= DERIVATIVES; k++) {
+		factorial *= float(k);
+		d[k] = cx_scale(horner[k], factorial);
+	}
+}
+
+// the point under this fragment, deep zoom adds the offset to the center in double-float
+CX pixel() {
+#ifdef DEEP_ZOOM
+	return cx_add(vec4(center.x, centerLow.x, center.y, centerLow.y), cx_from(offset));
+#else
+	return complex;
+#endif
+}
+
+// |a - b|, small enough for f32 once subtracted
+float cx_distance(CX a, CX b) {
+	return length(cx_to_vec2(cx_sub(a, b)));
 }
 ";
 
-fn build_uniforms(len: usize) -> String {
-    let mut result = String::new();
-    for i in 0..len {
-        result.push_str(format!("uniform vec2 root{};\n", i).as_str());
-        result.push_str(format!("uniform vec3 color{};\n", i).as_str());
-        result.push_str(format!("uniform vec2 dcoeff{};\n", i).as_str());
-    }
-    result
+const FRAGMENT_FOOTER: &str = "
+// fractional iteration count where |z - root| crossed the tolerance, interpolated on the
+// log distances of the last two iterates, orbits that didn't reach a root keep their count
+float smoothIterations(int iterations, CX z, CX previous, vec2 root) {
+	float current = cx_distance(z, cx_from(root));
+	float last = cx_distance(previous, cx_from(root));
+	if (!(current < tolerance && current > 0.0 && last > current)) {
+		return float(iterations);
+	}
+	float fraction = (log(tolerance) - log(last)) / (log(current) - log(last));
+	return float(iterations - 1) + clamp(fraction, 0.0, 1.0);
 }
 
-fn build_closes_root(len: usize) -> String {
-    let mut result = String::new();
-    result.push_str("void closestRoot(vec2 z, out vec4 color) {\n");
-    for i in 0..len {
-        result.push_str(format!("float dist{} = length(z - root{});\n", i, i).as_str());
-    }
-
-    for i in 0..len {
-        if i != len - 1 {
-            result.push_str("if (");
-            for j in (i + 1)..len {
-                result.push_str(format!("dist{} < dist{}", i, j).as_str());
-                if j != len - 1 {
-                    result.push_str(" && ");
-                }
-            }
-            result.push(')');
-        }
-        result.push_str(format!("{{\ncolor = vec4(color{}, 1.0);\n", i).as_str());
-        result.push_str("}\n");
-        if i != len - 1 {
-            result.push_str("else ");
-        }
-    }
-    result.push_str("}\n");
-    result
+bool converged(CX z, CX previous) {
+	if (cx_distance(z, previous) < tolerance) {
+		return true;
+	}
+	for (int i = 0; i < rootCount; i++) {
+		if (cx_distance(z, cx_from(roots[i])) < tolerance) {
+			return true;
+		}
+	}
+	return false;
 }
 
-fn build_evaluate_polynomial(len: usize) -> String {
-    let mut result = "vec2 evaluate_polynomial(vec2 z) {\nreturn ".to_string();
+// returns the number of iterations run before converging, `previous` is the iterate before z
+int iterate(inout CX z, out CX previous) {
+	previous = z;
+	for (int i = 0; i < maxIterations; i++) {
+		previous = z;
+		iteration(z);
+		if (converged(z, previous)) {
+			return i + 1;
+		}
+	}
+	return maxIterations;
+}
 
-    for _ in 0..len - 1 {
-        result.push_str("cx_mul(");
-    }
+void main() {
+	if (rootCount == 0) {
+		fragColor = vec4(0.0, 0.0, 0.0, 1.0);
+		return;
+	}
+	CX z = pixel();
+	CX previous;
+	int iterations = iterate(z, previous);
+	vec2 end = cx_to_vec2(z);
+	int root = closestRoot(end);
+	// orbits stuck in cycles or diverging end up away from every root, NaN included
+	if (!(length(end - roots[root]) <= convergenceRadius)) {
+		for (int i = 0; i < cyclePointCount; i++) {
+			if (length(end - cyclePoints[i]) <= convergenceRadius) {
+				fragColor = vec4(cycleColors[i], 1.0);
+				return;
+			}
+		}
+		fragColor = vec4(noBasinColor, 1.0);
+		return;
+	}
+#ifdef SMOOTH
+	float shadeIterations = smoothIterations(iterations, z, previous, roots[root]);
+#else
+	float shadeIterations = float(iterations);
+#endif
+	fragColor = vec4(shade(colors[root], shadeIterations), 1.0);
+}
+";
 
-    result.push_str("(z - root0)");
-    for i in 1..len {
-        result.push_str(format!(", (z - root{}))", i).as_str());
-    }
+// orbits stop when they escape or settle on a fixed point, which no longer has to be a root,
+// fixed points take the color of the root closest to them
+const FRAGMENT_NOVA: &str = "
+void main() {
+	if (rootCount == 0) {
+		fragColor = vec4(0.0, 0.0, 0.0, 1.0);
+		return;
+	}
+#ifdef NOVA_MANDELBROT
+	CX z = cx_from(roots[0]);
+	CX c = pixel();
+#else
+	CX z = pixel();
+	CX c = cx_from(novaConstant);
+#endif
+	for (int i = 0; i < maxIterations; i++) {
+		CX previous = z;
+		iteration(z);
+		z = cx_add(z, c);
+		vec2 end = cx_to_vec2(z);
+		if (dot(end, end) > NOVA_BAILOUT * NOVA_BAILOUT) {
+			fragColor = vec4(vec3(float(i + 1) / float(maxIterations)), 1.0);
+			return;
+		}
+		if (cx_distance(z, previous) < tolerance) {
+			fragColor = vec4(shade(colors[closestRoot(cx_to_vec2(z))], float(i + 1)), 1.0);
+			return;
+		}
+	}
+	fragColor = vec4(noBasinColor, 1.0);
+}
+";
 
-    result.push_str(";\n}\n");
-    result
+fn uniform_name(array: &str, index: usize) -> String {
+    format!("{}[{}]", array, index)
 }
 
-fn build_derivative_evaluation(len: usize) -> String {
+fn build_uniforms(key: ShaderKey) -> String {
     let mut result = String::new();
-    result.push_str("vec2 evaluate_derivative(vec2 z) {\nreturn ");
-    for i in 0..len {
-        if i != 0 {
-            result.push('+');
-        }
-        if i == len - 1 {
-            result.push_str(format!("dcoeff{}", i).as_str());
-        } else if i == len - 2 {
-            result.push_str(format!("cx_mul(dcoeff{}, z)", i).as_str());
-        } else {
-            result.push_str(format!("cx_mul(dcoeff{}, cx_pow(z, {}.0))", i, len - i - 1).as_str());
-        }
+    result.push_str(format!("#define MAX_ROOTS {}\n", key.max_roots).as_str());
+    result.push_str(format!("#define MAX_CYCLE_POINTS {}\n", MAX_CYCLE_POINTS).as_str());
+    result.push_str(format!("#define DERIVATIVES {}\n", key.method.derivatives()).as_str());
+    if key.nova != Nova::Off {
+        result.push_str(format!("#define NOVA_BAILOUT {:.1}\n", NOVA_BAILOUT).as_str());
+    }
+    if key.nova == Nova::Mandelbrot {
+        result.push_str("#define NOVA_MANDELBROT\n");
+    }
+    if key.smooth {
+        result.push_str("#define SMOOTH\n");
     }
-    result.push_str(";\n}\n");
+    if key.deep {
+        result.push_str("#define DEEP_ZOOM\n");
+    }
+    result.push_str("uniform vec2 roots[MAX_ROOTS];\n");
+    result.push_str("uniform vec3 colors[MAX_ROOTS];\n");
+    result.push_str("uniform vec2 coeffs[MAX_ROOTS + 1];\n");
+    result.push_str("uniform vec2 cyclePoints[MAX_CYCLE_POINTS];\n");
+    result.push_str("uniform vec3 cycleColors[MAX_CYCLE_POINTS];\n");
     result
 }
 
-fn build_fragment_shader(len: usize) -> String {
+fn build_iteration(method: Method) -> String {
+    format!(
+        "
+void iteration(inout CX z) {{
+	CX d[DERIVATIVES + 1];
+	evaluate_derivatives(z, d);
+	CX previous = z;
+	{}
+	z = cx_add(previous, cx_mul(cx_from(relaxation), cx_sub(z, previous)));
+}}
+",
+        method.glsl_step()
+    )
+}
+
+fn build_shading(shading: Shading) -> String {
+    format!(
+        "
+vec3 shade(vec3 color, float iterations) {{
+	float t = iterations / float(max(maxIterations, 1));
+	return color * ({});
+}}
+",
+        shading.glsl_brightness()
+    )
+}
+
+fn build_fragment_shader(key: ShaderKey) -> String {
     let mut result = String::new();
     result.push_str(FRAGMENT_HEADER);
-    result.push_str(build_uniforms(len).as_str());
-    result.push_str(build_closes_root(len).as_str());
-    result.push_str(build_evaluate_polynomial(len).as_str());
-    result.push_str(build_derivative_evaluation(len).as_str());
-    result.push_str(FRAGMENT_FOOTER);
+    result.push_str(if key.deep {
+        COMPLEX_DOUBLE
+    } else {
+        COMPLEX_SINGLE
+    });
+    result.push_str(build_uniforms(key).as_str());
+    result.push_str(FRAGMENT_FUNCTIONS);
+    result.push_str(build_iteration(key.method).as_str());
+    result.push_str(build_shading(key.shading).as_str());
+    match key.nova {
+        Nova::Off => result.push_str(FRAGMENT_FOOTER),
+        Nova::Julia | Nova::Mandelbrot => result.push_str(FRAGMENT_NOVA),
+    }
     result
 }
 
-fn create_material(len: usize) -> Option<Material> {
-    let mut params = Vec::new();
-    params.push(("maxIterations".to_owned(), UniformType::Int1));
-    params.push(("realRange".to_owned(), UniformType::Float2));
-    params.push(("imagRange".to_owned(), UniformType::Float2));
-    for i in 0..len {
-        params.push((format!("root{}", i), UniformType::Float2));
-        params.push((format!("color{}", i), UniformType::Float3));
-        params.push((format!("dcoeff{}", i), UniformType::Float2));
+fn create_material(key: ShaderKey) -> Result<Material, FractalError> {
+    let mut params = vec![
+        ("maxIterations".to_owned(), UniformType::Int1),
+        ("rootCount".to_owned(), UniformType::Int1),
+        ("relaxation".to_owned(), UniformType::Float2),
+        ("novaConstant".to_owned(), UniformType::Float2),
+        ("tolerance".to_owned(), UniformType::Float1),
+        ("gamma".to_owned(), UniformType::Float1),
+        ("convergenceRadius".to_owned(), UniformType::Float1),
+        ("noBasinColor".to_owned(), UniformType::Float3),
+        ("cyclePointCount".to_owned(), UniformType::Int1),
+        ("center".to_owned(), UniformType::Float2),
+        ("centerLow".to_owned(), UniformType::Float2),
+        ("extent".to_owned(), UniformType::Float2),
+        ("rotation".to_owned(), UniformType::Float2),
+    ];
+    // macroquad has no array uniforms, but GL resolves each element by its name
+    for i in 0..key.max_roots {
+        params.push((uniform_name("roots", i), UniformType::Float2));
+        params.push((uniform_name("colors", i), UniformType::Float3));
+    }
+    for i in 0..=key.max_roots {
+        params.push((uniform_name("coeffs", i), UniformType::Float2));
+    }
+    for i in 0..MAX_CYCLE_POINTS {
+        params.push((uniform_name("cyclePoints", i), UniformType::Float2));
+        params.push((uniform_name("cycleColors", i), UniformType::Float3));
     }
+    let fragment = build_fragment_shader(key);
     let material = load_material(
         VERTEX,
-        build_fragment_shader(len).as_str(),
+        fragment.as_str(),
         MaterialParams {
             uniforms: params,
             ..Default::default()
         },
     );
 
-    if material.is_err() {
-        match material.err().unwrap() {
-            ShaderError::CompilationError {
-                shader_type,
-                error_message,
-            } => {
-                error!("Error in {:?} shader:\n{}", shader_type, error_message);
-            }
-            err => error!("{:?}", err),
-        }
-        return None;
-    }
-    Some(material.unwrap())
+    material.map_err(|err| match err {
+        ShaderError::CompilationError {
+            shader_type,
+            error_message,
+        } => FractalError::ShaderCompilation {
+            shader_type,
+            source: match shader_type {
+                ShaderType::Vertex => VERTEX.to_owned(),
+                ShaderType::Fragment => fragment,
+            },
+            log: error_message,
+        },
+        ShaderError::LinkError(log) => FractalError::ShaderLink(log),
+        err => FractalError::Shader(format!("{:?}", err)),
+    })
 }
 
 impl FractalShader {
-    pub fn new(nb_roots: usize) -> Option<Self> {
-        let material = create_material(nb_roots)?;
-        Some(Self { shader: material })
+    pub fn new(key: ShaderKey) -> Result<Self, FractalError> {
+        let material = create_material(key)?;
+        Ok(Self {
+            shader: material,
+            key,
+        })
+    }
+
+    pub fn key(&self) -> ShaderKey {
+        self.key
+    }
+
+    pub fn delete(&mut self) {
+        self.shader.delete();
     }
 
     pub fn get_material(&self) -> &Material {
@@ -178,18 +483,62 @@ impl FractalShader {
     }
 
     pub fn set_material_roots(&mut self, roots: &[Vec2], colors: &[[f32; 3]]) {
-        for i in 0..roots.len() {
+        let len = roots.len().min(self.key.max_roots);
+        self.shader.set_uniform("rootCount", len as i32);
+        for i in 0..len {
             self.shader
-                .set_uniform(format!("root{}", i).as_str(), roots[i]);
+                .set_uniform(uniform_name("roots", i).as_str(), roots[i]);
             self.shader
-                .set_uniform(format!("color{}", i).as_str(), colors[i]);
+                .set_uniform(uniform_name("colors", i).as_str(), colors[i]);
         }
     }
 
-    pub fn set_material_derivative_coeff(&mut self, coefs: &[Complex<f32>]) {
-        for (i, coeff) in coefs.iter().enumerate() {
+    pub fn set_material_coefficients(&mut self, coefs: &[Complex<f32>]) {
+        for (i, coeff) in coefs.iter().take(self.key.max_roots + 1).enumerate() {
+            self.shader
+                .set_uniform(uniform_name("coeffs", i).as_str(), vec2(coeff.re, coeff.im));
+        }
+    }
+
+    pub fn set_material_relaxation(&mut self, relaxation: Vec2) {
+        self.shader.set_uniform("relaxation", relaxation);
+    }
+
+    pub fn set_material_nova_constant(&mut self, constant: Vec2) {
+        self.shader.set_uniform("novaConstant", constant);
+    }
+
+    pub fn set_material_tolerance(&mut self, tolerance: f32) {
+        self.shader.set_uniform("tolerance", tolerance);
+    }
+
+    pub fn set_material_gamma(&mut self, gamma: f32) {
+        self.shader.set_uniform("gamma", gamma);
+    }
+
+    pub fn set_material_no_basin(&mut self, convergence_radius: f32, color: [f32; 3]) {
+        self.shader
+            .set_uniform("convergenceRadius", convergence_radius);
+        self.shader.set_uniform("noBasinColor", color);
+    }
+
+    // every point carries the color of its cycle
+    pub fn set_material_cycles(&mut self, cycles: &[Cycle]) {
+        let points: Vec<_> = cycles
+            .iter()
+            .enumerate()
+            .flat_map(|(index, cycle)| cycle.points.iter().map(move |point| (point, index)))
+            .take(MAX_CYCLE_POINTS)
+            .collect();
+        self.shader
+            .set_uniform("cyclePointCount", points.len() as i32);
+        for (i, (point, index)) in points.into_iter().enumerate() {
+            self.shader.set_uniform(
+                uniform_name("cyclePoints", i).as_str(),
+                vec2(point.re, point.im),
+            );
             self.shader
-                .set_uniform(format!("dcoeff{}", i).as_str(), vec2(coeff.re, coeff.im));
+                .set_uniform(uniform_name("cycleColors", i).as_str(), cycle_color(index));
         }
     }
 
@@ -197,8 +546,15 @@ impl FractalShader {
         self.shader.set_uniform("maxIterations", max_iter);
     }
 
-    pub fn set_material_range(&mut self, real_range: Vec2, imag_range: Vec2) {
-        self.shader.set_uniform("realRange", real_range);
-        self.shader.set_uniform("imagRange", imag_range);
+    pub fn set_material_viewport(&mut self, viewport: &Viewport) {
+        let center = viewport.get_center();
+        let high = center.as_vec2();
+        self.shader.set_uniform("center", high);
+        self.shader
+            .set_uniform("centerLow", (center - high.as_dvec2()).as_vec2());
+        self.shader
+            .set_uniform("extent", viewport.extent().as_vec2());
+        self.shader
+            .set_uniform("rotation", Vec2::from_angle(viewport.get_rotation()));
     }
 }
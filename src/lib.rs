@@ -1,10 +1,54 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
+mod error;
+pub use error::FractalError;
+
+mod real;
+pub use real::{BigFloat, Real};
+
 mod polynomial;
 pub use polynomial::Polynomial;
 
+mod method;
+pub use method::{
+    Chebyshev, Halley, Householder, IterationMethod, Method, Newton, Schroeder,
+    MAX_HOUSEHOLDER_ORDER,
+};
+
+mod nova;
+pub use nova::{Nova, NOVA_BAILOUT};
+
+mod shading;
+pub use shading::Shading;
+
+mod cycle;
+pub use cycle::{cycle_color, Cycle, MAX_CYCLE_PERIOD, MAX_CYCLE_POINTS};
+
+mod viewport;
+pub use viewport::{AspectPolicy, Viewport, MIN_SCALE};
+
 mod newtonfractal;
 pub use newtonfractal::NewtonFractal;
 
 mod fractal_shader;
-pub use fractal_shader::FractalShader;
+pub use fractal_shader::{FractalShader, ShaderKey, MAX_ROOTS};
+
+mod shader_cache;
+pub use shader_cache::{
+    CompiledShader, ShaderCache, ShaderCacheStats, DEFAULT_SHADER_CACHE_CAPACITY,
+};
+
+mod renderer;
+pub use renderer::{
+    CancelToken, CpuRenderer, Orbit, OrbitState, TileOptions, DEFAULT_CONVERGENCE_RADIUS,
+    DEFAULT_TOLERANCE,
+};
+
+mod export;
+pub use export::{export_png, read_png_parameters, write_png};
+
+mod scene;
+pub use scene::{Scene, SceneError, SCENE_VERSION};
+
+mod share;
+pub use share::{replace_url_params, scene_from_url_params, scene_to_url_params, url_query};
@@ -1,32 +1,192 @@
 use egui::RichText;
 use macroquad::{
     color::*,
-    input::{is_key_down, is_mouse_button_down, mouse_position},
+    input::{
+        is_key_down, is_mouse_button_down, is_mouse_button_pressed, mouse_position, mouse_wheel,
+        touches, MouseButton, TouchPhase,
+    },
     material::{gl_use_default_material, gl_use_material},
-    math::vec2,
+    math::{vec2, Vec2},
+    miniquad::error,
     rand::{self},
     shapes::{draw_circle, draw_rectangle},
-    window::{next_frame, screen_height, screen_width},
+    time::get_time,
+    window::{clear_background, next_frame, screen_height, screen_width},
+};
+use newton_fractal::{
+    cycle_color, replace_url_params, scene_from_url_params, scene_to_url_params, write_png,
+    AspectPolicy, CancelToken, CpuRenderer, Method, NewtonFractal, Nova, Shading, TileOptions,
+    MAX_CYCLE_PERIOD, MAX_HOUSEHOLDER_ORDER,
+};
+use std::{
+    fs::File,
+    io::{self, BufWriter},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
 };
-use newton_fractal::NewtonFractal;
 
 const ROOT_RADIUS: f32 = 8.;
+// view scale applied per wheel notch
+const ZOOM_STEP: f32 = 1.1;
+// fraction of the view moved per frame while an arrow key is held
+const KEY_PAN_STEP: f32 = 0.005;
+// radians turned per frame while q or e is held
+const KEY_ROTATE_STEP: f32 = 0.02;
+// seconds the scene has to stay unchanged before the url follows it
+const URL_IDLE_DELAY: f64 = 0.5;
+
+// on the web build the query string holds the scene, natively these are the command line arguments
+fn restore_url_state(fractal: &mut NewtonFractal) {
+    let args = quad_url::get_program_parameters();
+    let params: Vec<(&str, &str)> = args
+        .iter()
+        .skip(1)
+        .filter_map(|arg| match quad_url::easy_parse(arg)? {
+            (key, Some(value)) => Some((key, value)),
+            _ => None,
+        })
+        .collect();
+    if params.is_empty() {
+        return;
+    }
+    let scene = scene_from_url_params(params, &fractal.scene());
+    if let Err(err) = scene.and_then(|scene| fractal.set_scene(&scene)) {
+        error!("Invalid fractal in url: {}", err);
+    }
+}
+
+struct UrlState {
+    written: Vec<(&'static str, String)>,
+    pending: Vec<(&'static str, String)>,
+    changed_at: f64,
+}
+
+impl UrlState {
+    fn new(fractal: &NewtonFractal) -> Self {
+        let params = scene_to_url_params(&fractal.scene());
+        Self {
+            written: params.clone(),
+            pending: params,
+            changed_at: get_time(),
+        }
+    }
+
+    // zooms, pans and drags change the scene every frame, the url is only rewritten once
+    // they have settled so the history isn't flooded
+    fn update(&mut self, fractal: &NewtonFractal) {
+        let params = scene_to_url_params(&fractal.scene());
+        if params != self.pending {
+            self.pending = params;
+            self.changed_at = get_time();
+            return;
+        }
+        let interacting = [MouseButton::Left, MouseButton::Middle, MouseButton::Right]
+            .into_iter()
+            .any(is_mouse_button_down);
+        if self.pending != self.written
+            && !interacting
+            && get_time() - self.changed_at >= URL_IDLE_DELAY
+        {
+            replace_url_params(&self.pending);
+            self.written = self.pending.clone();
+        }
+    }
+}
+
+struct ExportJob {
+    path: String,
+    handle: JoinHandle<io::Result<bool>>,
+    progress: Arc<[AtomicUsize; 2]>,
+    cancel: CancelToken,
+}
+
+impl ExportJob {
+    // renders on a separate thread, the result is false if the export was cancelled
+    fn start(renderer: CpuRenderer, path: String, width: u16, height: u16) -> Self {
+        let progress = Arc::new([AtomicUsize::new(0), AtomicUsize::new(1)]);
+        let cancel = CancelToken::new();
+        let handle = {
+            let path = path.clone();
+            let progress = progress.clone();
+            let cancel = cancel.clone();
+            thread::spawn(move || {
+                let image = renderer.render_tiled(
+                    width,
+                    height,
+                    TileOptions::default(),
+                    |done, total| {
+                        progress[0].store(done, Ordering::Relaxed);
+                        progress[1].store(total, Ordering::Relaxed);
+                    },
+                    &cancel,
+                );
+                match image {
+                    Some(image) => {
+                        write_png(&renderer, &image, BufWriter::new(File::create(path)?))?;
+                        Ok(true)
+                    }
+                    None => Ok(false),
+                }
+            })
+        };
+        Self {
+            path,
+            handle,
+            progress,
+            cancel,
+        }
+    }
+
+    fn progress(&self) -> f32 {
+        self.progress[0].load(Ordering::Relaxed) as f32
+            / self.progress[1].load(Ordering::Relaxed) as f32
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Pinch {
+    distance: f32,
+    // direction from the first finger to the second one
+    angle: f32,
+    midpoint: Vec2,
+}
+
+// two active touches, ordered by id so the angle doesn't flip between frames
+fn pinch() -> Option<Pinch> {
+    let mut touches: Vec<_> = touches()
+        .into_iter()
+        .filter(|touch| !matches!(touch.phase, TouchPhase::Ended | TouchPhase::Cancelled))
+        .collect();
+    touches.sort_by_key(|touch| touch.id);
+    match touches.as_slice() {
+        [a, b] => {
+            let offset = b.position - a.position;
+            Some(Pinch {
+                distance: offset.length(),
+                angle: offset.y.atan2(offset.x),
+                midpoint: (a.position + b.position) / 2.0,
+            })
+        }
+        _ => None,
+    }
+}
 
-fn map(x: f32, in_min: f32, in_max: f32, out_min: f32, out_max: f32) -> f32 {
-    (x - in_min) * (out_max - out_min) / (in_max - in_min) + out_min
+fn under_cursor(root: Vec2, cursor: Vec2) -> bool {
+    let offset = (root - cursor).abs();
+    offset.x < ROOT_RADIUS * 1.2 && offset.y < ROOT_RADIUS * 1.2
 }
 
-fn draw_roots(fractal: &mut NewtonFractal) {
-    let pos = mouse_position();
-    let real_range = fractal.get_real_range();
-    let imag_range = fractal.get_imag_range();
+fn draw_roots(fractal: &NewtonFractal) {
+    let cursor = Vec2::from(mouse_position());
     for root in fractal.get_roots() {
-        let x = map(root.x, real_range.x, real_range.y, 0.0, screen_width());
-        let y = map(root.y, imag_range.x, imag_range.y, 0.0, screen_height());
-        if (pos.0 - x).abs() < ROOT_RADIUS * 1.2 && (pos.1 - y).abs() < ROOT_RADIUS * 1.2 {
-            draw_circle(x, y, ROOT_RADIUS, WHITE);
+        let position = fractal.get_viewport().complex_to_screen(root.as_dvec2());
+        if under_cursor(position, cursor) {
+            draw_circle(position.x, position.y, ROOT_RADIUS, WHITE);
         } else {
-            draw_circle(x, y, ROOT_RADIUS, BLACK);
+            draw_circle(position.x, position.y, ROOT_RADIUS, BLACK);
         }
     }
 }
@@ -55,6 +215,19 @@ fn draw_roots(fractal: &mut NewtonFractal) {
 // 	[0.0, 1.0, 1.0],
 // ],
 
+async fn show_error(message: String) {
+    loop {
+        clear_background(BLACK);
+        egui_macroquad::ui(|ctx| {
+            egui::Window::new("Error").show(ctx, |ui| {
+                ui.label(&message);
+            });
+        });
+        egui_macroquad::draw();
+        next_frame().await
+    }
+}
+
 #[macroquad::main("Newton Fractal")]
 async fn main() {
     let mut iter = 30;
@@ -72,128 +245,288 @@ async fn main() {
         vec2(-1.0, 1.0),
     );
 
-    if fractal.is_none() {
-        panic!("Error creating fractal");
-    }
+    let mut fractal = match fractal {
+        Ok(fractal) => fractal,
+        Err(err) => return show_error(format!("Error creating fractal: {}", err)).await,
+    };
+    let mut fractal_status = String::new();
+    // scenes are fitted to the screen, which needs its size first
+    fractal
+        .get_viewport_mut()
+        .set_size(vec2(screen_width(), screen_height()));
+    restore_url_state(&mut fractal);
+    iter = fractal.get_max_iterations();
+    let mut url_state = UrlState::new(&fractal);
 
-    let mut fractal = fractal.unwrap();
     let mut drag_lock = false;
     let mut drag_index = -1;
+    let mut panning = false;
+    let mut last_mouse = Vec2::from(mouse_position());
+    let mut pointer_over_ui = false;
+    let mut last_pinch: Option<Pinch> = None;
+
+    let mut export_path = "newton_fractal.png".to_owned();
+    let mut export_size = (1920u16, 1080u16);
+    let mut export_job: Option<ExportJob> = None;
+    let mut export_status = String::new();
+
+    let mut scene_path = "scene.json".to_owned();
+    let mut scene_status = String::new();
 
     loop {
-        if is_mouse_button_down(macroquad::input::MouseButton::Left) {
-            let real_range = fractal.get_real_range();
-            let imag_range = fractal.get_imag_range();
+        // resizing the window keeps the scale and only uncovers more or less of the plane
+        fractal
+            .get_viewport_mut()
+            .set_size(vec2(screen_width(), screen_height()));
+        let mouse = Vec2::from(mouse_position());
+        let viewport = fractal.get_viewport().clone();
+        if is_mouse_button_down(MouseButton::Left) {
             if add_root {
                 add_root = false;
-                let x = map(
-                    mouse_position().0,
-                    0.0,
-                    screen_width(),
-                    real_range.x,
-                    real_range.y,
-                );
-                let y = map(
-                    mouse_position().1,
-                    0.0,
-                    screen_height(),
-                    imag_range.x,
-                    imag_range.y,
-                );
-                fractal.add_root(
-                    vec2(x, y),
-                    [
-                        rand::gen_range(0., 255.),
-                        rand::gen_range(0., 255.),
-                        rand::gen_range(0., 255.),
-                    ],
-                );
+                let color = [
+                    rand::gen_range(0., 1.),
+                    rand::gen_range(0., 1.),
+                    rand::gen_range(0., 1.),
+                ];
+                if let Err(err) =
+                    fractal.add_root(viewport.screen_to_complex(mouse).as_vec2(), color)
+                {
+                    fractal_status = err.to_string();
+                }
             } else if !drag_lock {
                 for (i, root) in fractal.get_roots().iter().enumerate() {
-                    let x = map(root.x, real_range.x, real_range.y, 0.0, screen_width());
-                    let y = map(root.y, imag_range.x, imag_range.y, 0.0, screen_height());
-                    if (mouse_position().0 - x).abs() < ROOT_RADIUS * 1.2
-                        && (mouse_position().1 - y).abs() < ROOT_RADIUS * 1.2
-                    {
+                    if under_cursor(viewport.complex_to_screen(root.as_dvec2()), mouse) {
                         drag_index = i as i32;
                         break;
                     }
                 }
+                // dragging empty space moves the view instead
+                panning = drag_index == -1 && !pointer_over_ui;
                 drag_lock = true;
             } else if drag_index != -1 {
-                let root = &mut fractal.get_roots()[drag_index as usize];
-                root.x = map(
-                    mouse_position().0,
-                    0.0,
-                    screen_width(),
-                    real_range.x,
-                    real_range.y,
-                );
-                root.y = map(
-                    mouse_position().1,
-                    0.0,
-                    screen_height(),
-                    imag_range.x,
-                    imag_range.y,
-                );
+                fractal.get_roots_mut()[drag_index as usize] =
+                    viewport.screen_to_complex(mouse).as_vec2();
             }
         } else {
             drag_lock = false;
             drag_index = -1;
+            panning = false;
+        }
+        if (is_mouse_button_pressed(MouseButton::Middle)
+            || is_mouse_button_pressed(MouseButton::Right))
+            && !pointer_over_ui
+        {
+            panning = true;
         }
+        if !is_mouse_button_down(MouseButton::Left)
+            && !is_mouse_button_down(MouseButton::Middle)
+            && !is_mouse_button_down(MouseButton::Right)
+        {
+            panning = false;
+        }
+        // keep the point grabbed under the cursor, pinches zoom rather than pan
+        if panning && pinch().is_none() {
+            fractal.get_viewport_mut().drag(mouse - last_mouse);
+        }
+        last_mouse = mouse;
 
+        // arrow keys move by a fraction of the screen so they keep working when zoomed in
+        let step = KEY_PAN_STEP * viewport.get_size();
         if is_key_down(macroquad::miniquad::KeyCode::Left) {
             // go left
-            let real_range = fractal.get_real_range_mut();
-            real_range.x -= 0.01;
-            real_range.y -= 0.01;
+            fractal.get_viewport_mut().drag(vec2(step.x, 0.0));
         }
         if is_key_down(macroquad::miniquad::KeyCode::Right) {
             // go right
-            let real_range = fractal.get_real_range_mut();
-            real_range.x += 0.01;
-            real_range.y += 0.01;
+            fractal.get_viewport_mut().drag(vec2(-step.x, 0.0));
         }
         if is_key_down(macroquad::miniquad::KeyCode::Down) {
             // go down
-            let imag_range = fractal.get_imag_range_mut();
-            imag_range.x += 0.01;
-            imag_range.y += 0.01;
+            fractal.get_viewport_mut().drag(vec2(0.0, -step.y));
         }
         if is_key_down(macroquad::miniquad::KeyCode::Up) {
             // go up
-            let imag_range = fractal.get_imag_range_mut();
-            imag_range.x -= 0.01;
-            imag_range.y -= 0.01;
+            fractal.get_viewport_mut().drag(vec2(0.0, step.y));
+        }
+        // q and e turn the view around the center of the screen
+        let turn = match (
+            is_key_down(macroquad::miniquad::KeyCode::Q),
+            is_key_down(macroquad::miniquad::KeyCode::E),
+        ) {
+            (true, false) => KEY_ROTATE_STEP,
+            (false, true) => -KEY_ROTATE_STEP,
+            _ => 0.0,
+        };
+        if turn != 0.0 {
+            let viewport = fractal.get_viewport_mut();
+            viewport.rotate(viewport.get_size() / 2.0, turn);
         }
 
         // gui
         egui_macroquad::ui(|ctx| {
+            pointer_over_ui = ctx.is_pointer_over_area() || ctx.wants_pointer_input();
             egui::Window::new("Configuration").show(ctx, |ui| {
                 ui.vertical_centered(|ui| {
                     ui.label(RichText::new("Render").strong());
                 });
                 ui.label("Iterations");
                 ui.add(egui::DragValue::new(&mut iter).speed(1.0));
-                ui.separator();
-                ui.label("Real Range");
+                ui.label("Method");
+                let mut method = fractal.get_method();
+                egui::ComboBox::from_id_source("method")
+                    .selected_text(method.name())
+                    .show_ui(ui, |ui| {
+                        for option in Method::ALL {
+                            let selected = method.name() == option.name();
+                            if ui.selectable_label(selected, option.name()).clicked() && !selected {
+                                method = option;
+                            }
+                        }
+                    });
+                if let Method::Householder(householder) = &mut method {
+                    ui.horizontal(|ui| {
+                        ui.label("Order");
+                        ui.add(
+                            egui::DragValue::new(&mut householder.order)
+                                .clamp_range(1..=MAX_HOUSEHOLDER_ORDER),
+                        );
+                    });
+                }
+                fractal.set_method(method);
+                ui.label("Relaxation");
+                ui.horizontal(|ui| {
+                    let relaxation = fractal.get_relaxation_mut();
+                    ui.add(egui::DragValue::new(&mut relaxation.x).speed(0.01));
+                    ui.add(egui::DragValue::new(&mut relaxation.y).speed(0.01));
+                });
+                ui.label("Nova");
+                let mut nova = fractal.get_nova();
+                egui::ComboBox::from_id_source("nova")
+                    .selected_text(nova.name())
+                    .show_ui(ui, |ui| {
+                        for option in Nova::ALL {
+                            ui.selectable_value(&mut nova, option, option.name());
+                        }
+                    });
+                fractal.set_nova(nova);
+                if nova == Nova::Julia {
+                    ui.horizontal(|ui| {
+                        ui.label("c");
+                        let constant = fractal.get_nova_constant_mut();
+                        ui.add(egui::DragValue::new(&mut constant.x).speed(0.01));
+                        ui.add(egui::DragValue::new(&mut constant.y).speed(0.01));
+                    });
+                }
+                ui.label("Tolerance");
+                ui.add(
+                    egui::Slider::new(fractal.get_tolerance_mut(), 0.0..=0.1)
+                        .logarithmic(true)
+                        .smallest_positive(1e-8),
+                );
+                ui.label("Shading");
+                let mut shading = fractal.get_shading();
+                egui::ComboBox::from_id_source("shading")
+                    .selected_text(shading.name())
+                    .show_ui(ui, |ui| {
+                        for option in Shading::ALL {
+                            ui.selectable_value(&mut shading, option, option.name());
+                        }
+                    });
+                fractal.set_shading(shading);
+                if shading == Shading::Gamma {
+                    ui.add(
+                        egui::Slider::new(fractal.get_gamma_mut(), 0.05..=10.0)
+                            .logarithmic(true)
+                            .text("Gamma"),
+                    );
+                }
+                if shading != Shading::Flat {
+                    ui.checkbox(fractal.get_smooth_mut(), "Smooth");
+                }
+                ui.label("Convergence Radius");
+                ui.add(
+                    egui::Slider::new(fractal.get_convergence_radius_mut(), 1e-4..=10.0)
+                        .logarithmic(true),
+                );
                 ui.horizontal(|ui| {
-                    let range = fractal.get_real_range_mut();
-                    ui.add(egui::DragValue::new(&mut range.x).speed(0.01));
-                    ui.add(egui::DragValue::new(&mut range.y).speed(0.01));
+                    ui.label("No basin");
+                    ui.color_edit_button_rgb(fractal.get_no_basin_color_mut());
                 });
-                ui.label("Imaginary Range");
+                if nova == Nova::Off {
+                    ui.horizontal(|ui| {
+                        ui.label("Cycle period");
+                        ui.add(
+                            egui::DragValue::new(fractal.get_max_cycle_period_mut())
+                                .clamp_range(0..=MAX_CYCLE_PERIOD),
+                        );
+                    });
+                }
+                let stats = fractal.shader_cache_stats();
+                ui.label(format!(
+                    "Shaders: {}/{} cached, {} hits, {} misses, {} failures, {} evictions",
+                    stats.entries,
+                    stats.capacity,
+                    stats.hits,
+                    stats.misses,
+                    stats.failures,
+                    stats.evictions
+                ));
+                ui.separator();
+                let viewport = fractal.get_viewport_mut();
+                // dragging moves the center by about a hundredth of the view per point
+                let speed = 0.01 * viewport.get_scale();
+                ui.label("Center");
                 ui.horizontal(|ui| {
-                    let range = fractal.get_imag_range_mut();
-                    ui.add(egui::DragValue::new(&mut range.x).speed(0.01));
-                    ui.add(egui::DragValue::new(&mut range.y).speed(0.01));
+                    // the precise center is only replaced once edited
+                    let mut center = viewport.get_center();
+                    let changed = ui
+                        .add(egui::DragValue::new(&mut center.x).speed(speed))
+                        .changed()
+                        | ui.add(egui::DragValue::new(&mut center.y).speed(speed))
+                            .changed();
+                    if changed {
+                        viewport.set_center(center);
+                    }
                 });
+                ui.label("Scale");
+                let mut scale = viewport.get_scale();
+                let range = viewport.min_scale()..=100.0;
+                if ui
+                    .add(egui::Slider::new(&mut scale, range).logarithmic(true))
+                    .changed()
+                {
+                    viewport.set_scale(scale);
+                }
+                if viewport.needs_deep_zoom() {
+                    ui.label("Deep zoom, rendering in double-float");
+                }
+                ui.label("Aspect");
+                let mut aspect = viewport.get_aspect();
+                egui::ComboBox::from_id_source("aspect")
+                    .selected_text(aspect.name())
+                    .show_ui(ui, |ui| {
+                        for option in AspectPolicy::ALL {
+                            ui.selectable_value(&mut aspect, option, option.name());
+                        }
+                    });
+                viewport.set_aspect(aspect);
+                ui.label("Rotation");
+                let mut degrees = viewport.get_rotation().to_degrees();
+                let slider = egui::Slider::new(&mut degrees, -180.0..=180.0).suffix("°");
+                // only write back edits, the conversion isn't exact
+                if ui.add(slider).changed() {
+                    viewport.set_rotation(degrees.to_radians());
+                }
 
                 ui.vertical_centered(|ui| {
                     ui.label(RichText::new("Roots").strong());
                 });
                 if ui.button("Add Root").clicked() {
                     add_root = !add_root;
+                    fractal_status.clear();
+                }
+                if !fractal_status.is_empty() {
+                    ui.colored_label(egui::Color32::RED, &fractal_status);
                 }
                 for i in 0..fractal.len() {
                     if i >= fractal.len() {
@@ -201,25 +534,170 @@ async fn main() {
                     }
                     ui.label(format!("Root {}", i + 1));
                     ui.horizontal(|ui| {
-                        ui.add(egui::DragValue::new(&mut fractal.get_roots()[i].x).speed(0.01));
-                        ui.add(egui::DragValue::new(&mut fractal.get_roots()[i].y).speed(0.01));
-                        ui.color_edit_button_rgb(&mut fractal.get_colors()[i]);
+                        ui.add(egui::DragValue::new(&mut fractal.get_roots_mut()[i].x).speed(0.01));
+                        ui.add(egui::DragValue::new(&mut fractal.get_roots_mut()[i].y).speed(0.01));
+                        ui.color_edit_button_rgb(&mut fractal.get_colors_mut()[i]);
                         if ui.button("Remove").clicked() {
-                            fractal.remove_root(i);
+                            if let Err(err) = fractal.remove_root(i) {
+                                fractal_status = err.to_string();
+                            }
                         }
                     });
                 }
+
+                // the web build has neither threads nor a filesystem to export to
+                if cfg!(not(target_arch = "wasm32")) {
+                    ui.vertical_centered(|ui| {
+                        ui.label(RichText::new("Scene").strong());
+                    });
+                    ui.text_edit_singleline(&mut scene_path);
+                    ui.horizontal(|ui| {
+                        if ui.button("Save").clicked() {
+                            fractal.set_max_iterations(iter);
+                            scene_status = match fractal.save_scene(&scene_path) {
+                                Ok(()) => format!("Saved {}", scene_path),
+                                Err(err) => format!("Save failed: {}", err),
+                            };
+                        }
+                        if ui.button("Open").clicked() {
+                            scene_status = match fractal.load_scene(&scene_path) {
+                                Ok(()) => {
+                                    iter = fractal.get_max_iterations();
+                                    format!("Opened {}", scene_path)
+                                }
+                                Err(err) => format!("Open failed: {}", err),
+                            };
+                        }
+                    });
+                    if !scene_status.is_empty() {
+                        ui.label(&scene_status);
+                    }
+
+                    ui.vertical_centered(|ui| {
+                        ui.label(RichText::new("Export").strong());
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut export_size.0).clamp_range(1..=u16::MAX));
+                        ui.label("x");
+                        ui.add(egui::DragValue::new(&mut export_size.1).clamp_range(1..=u16::MAX));
+                    });
+                    ui.text_edit_singleline(&mut export_path);
+                    // much slower, for posters zoomed in further than the screen can, the
+                    // view zooms beyond what the screen shows then
+                    let viewport = fractal.get_viewport_mut();
+                    let mut arbitrary_precision = viewport.get_arbitrary_precision();
+                    if ui
+                        .checkbox(&mut arbitrary_precision, "Arbitrary precision")
+                        .changed()
+                    {
+                        viewport.set_arbitrary_precision(arbitrary_precision);
+                    }
+                    match &export_job {
+                        Some(job) => {
+                            ui.add(egui::ProgressBar::new(job.progress()).show_percentage());
+                            if ui.button("Cancel").clicked() {
+                                job.cancel.cancel();
+                            }
+                        }
+                        None => {
+                            if ui.button("Export image").clicked() {
+                                export_status.clear();
+                                // the export shows the same view, fitted to its own size
+                                let mut renderer = CpuRenderer::from_fractal(&fractal);
+                                renderer.set_viewport(
+                                    &fractal.get_viewport().with_size(vec2(
+                                        export_size.0 as f32,
+                                        export_size.1 as f32,
+                                    )),
+                                );
+                                renderer.set_arbitrary_precision(
+                                    fractal.get_viewport().get_arbitrary_precision(),
+                                );
+                                export_job = Some(ExportJob::start(
+                                    renderer,
+                                    export_path.clone(),
+                                    export_size.0,
+                                    export_size.1,
+                                ));
+                            }
+                        }
+                    }
+                    if !export_status.is_empty() {
+                        ui.label(&export_status);
+                    }
+                }
             });
+
+            if fractal.get_max_cycle_period() > 0 && fractal.get_nova() == Nova::Off {
+                egui::SidePanel::right("cycles").show(ctx, |ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.label(RichText::new("Cycles").strong());
+                    });
+                    if fractal.get_cycles().is_empty() {
+                        ui.label("No attracting cycle found");
+                    }
+                    for (i, cycle) in fractal.get_cycles().iter().enumerate() {
+                        let [r, g, b] = cycle_color(i).map(|channel| (channel * 255.0) as u8);
+                        ui.colored_label(
+                            egui::Color32::from_rgb(r, g, b),
+                            format!("Cycle {}: period {}", i + 1, cycle.period()),
+                        );
+                        ui.label(format!("|multiplier| {:.3}", cycle.multiplier.norm()));
+                        for point in &cycle.points {
+                            ui.label(format!("{:.4} {:+.4}i", point.re, point.im));
+                        }
+                    }
+                });
+            }
         });
 
+        // zoom around the point under the cursor, or between the fingers of a pinch
+        let wheel = mouse_wheel().1;
+        if wheel != 0.0 && !pointer_over_ui {
+            let viewport = fractal.get_viewport_mut();
+            let factor = if wheel > 0.0 {
+                1.0 / ZOOM_STEP
+            } else {
+                ZOOM_STEP
+            };
+            viewport.zoom(mouse_position().into(), factor);
+        }
+        // the plane follows both fingers, scaling with their distance and turning with them
+        let pinch = pinch();
+        if let (Some(pinch), Some(previous)) = (pinch, last_pinch) {
+            let viewport = fractal.get_viewport_mut();
+            if pinch.distance > 0.0 {
+                viewport.zoom(pinch.midpoint, previous.distance / pinch.distance);
+            }
+            viewport.rotate(pinch.midpoint, previous.angle - pinch.angle);
+        }
+        last_pinch = pinch;
+
+        if export_job
+            .as_ref()
+            .map_or(false, |job| job.handle.is_finished())
+        {
+            let job = export_job.take().unwrap();
+            export_status = match job.handle.join() {
+                Ok(Ok(true)) => format!("Saved {}", job.path),
+                Ok(Ok(false)) => "Export cancelled".to_owned(),
+                Ok(Err(err)) => format!("Export failed: {}", err),
+                Err(_) => "Export failed".to_owned(),
+            };
+        }
+
         fractal.set_max_iterations(iter);
-        fractal.update();
+        if let Err(err) = fractal.update() {
+            fractal_status = err.to_string();
+        }
+
+        url_state.update(&fractal);
 
         gl_use_material(*fractal.get_material());
         draw_rectangle(0.0, 0., screen_width(), screen_height(), WHITE);
         gl_use_default_material();
 
-        draw_roots(&mut fractal);
+        draw_roots(&fractal);
 
         egui_macroquad::draw();
 
@@ -0,0 +1,292 @@
+use num_complex::Complex;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+use crate::Real;
+
+// higher orders need more derivatives than f32 coefficients can carry accurately
+pub const MAX_HOUSEHOLDER_ORDER: u32 = 8;
+
+// a root-finding scheme, implemented once for the CPU renderer and once as GLSL
+pub trait IterationMethod {
+    // highest derivative of the polynomial used by a step
+    fn derivatives(&self) -> usize;
+
+    // d[k] is the k-th derivative of the polynomial at z, the same step in every precision
+    fn step<T: Real>(&self, z: Complex<T>, d: &[Complex<T>]) -> Complex<T>
+    where
+        Self: Sized;
+
+    // GLSL statements updating `z`, with the same `d` as `step`, complex values are CX and
+    // only go through the cx_ functions so the step also runs in double-float
+    fn glsl_step(&self) -> String;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Newton;
+
+impl IterationMethod for Newton {
+    fn derivatives(&self) -> usize {
+        1
+    }
+
+    fn step<T: Real>(&self, z: Complex<T>, d: &[Complex<T>]) -> Complex<T> {
+        z - &d[0] / &d[1]
+    }
+
+    fn glsl_step(&self) -> String {
+        "z = cx_sub(z, cx_div(d[0], d[1]));".to_owned()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Halley;
+
+impl IterationMethod for Halley {
+    fn derivatives(&self) -> usize {
+        2
+    }
+
+    fn step<T: Real>(&self, z: Complex<T>, d: &[Complex<T>]) -> Complex<T> {
+        let two = T::from_f64(2.0);
+        z - (&d[0] * &d[1]).scale(two.clone()) / ((&d[1] * &d[1]).scale(two) - &d[0] * &d[2])
+    }
+
+    fn glsl_step(&self) -> String {
+        "z = cx_sub(z, cx_div(cx_scale(cx_mul(d[0], d[1]), 2.0), cx_sub(cx_scale(cx_mul(d[1], d[1]), 2.0), cx_mul(d[0], d[2]))));"
+            .to_owned()
+    }
+}
+
+// newton applied to p / p', converges quadratically on multiple roots too
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Schroeder;
+
+impl IterationMethod for Schroeder {
+    fn derivatives(&self) -> usize {
+        2
+    }
+
+    fn step<T: Real>(&self, z: Complex<T>, d: &[Complex<T>]) -> Complex<T> {
+        z - &d[0] * &d[1] / (&d[1] * &d[1] - &d[0] * &d[2])
+    }
+
+    fn glsl_step(&self) -> String {
+        "z = cx_sub(z, cx_div(cx_mul(d[0], d[1]), cx_sub(cx_mul(d[1], d[1]), cx_mul(d[0], d[2]))));"
+            .to_owned()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Chebyshev;
+
+impl IterationMethod for Chebyshev {
+    fn derivatives(&self) -> usize {
+        2
+    }
+
+    fn step<T: Real>(&self, z: Complex<T>, d: &[Complex<T>]) -> Complex<T> {
+        let newton = &d[0] / &d[1];
+        let correction = (&d[0] * &d[2] / (&d[1] * &d[1])).scale(T::from_f64(0.5));
+        z - newton * (Complex::new(T::one(), T::zero()) + correction)
+    }
+
+    fn glsl_step(&self) -> String {
+        "z = cx_sub(z, cx_mul(cx_div(d[0], d[1]), cx_add(cx_from(vec2(1.0, 0.0)), cx_scale(cx_div(cx_mul(d[0], d[2]), cx_mul(d[1], d[1])), 0.5))));"
+            .to_owned()
+    }
+}
+
+// z + n (1/p)^(n-1) / (1/p)^(n), order 1 is newton and order 2 is halley
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Householder {
+    pub order: u32,
+}
+
+impl Default for Householder {
+    fn default() -> Self {
+        Self { order: 3 }
+    }
+}
+
+fn binomial(n: usize, k: usize) -> f32 {
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f32 / (i + 1) as f32)
+}
+
+// the derivatives of 1/p are scaled by powers of p, h[k] = p^(k+1) (1/p)^(k), which keeps
+// them finite near the roots:
+// h[0] = 1, h[k] = -sum_{j<k} C(k, j) h[j] p^(k-j-1) d[k-j]
+impl IterationMethod for Householder {
+    fn derivatives(&self) -> usize {
+        self.order as usize
+    }
+
+    fn step<T: Real>(&self, z: Complex<T>, d: &[Complex<T>]) -> Complex<T> {
+        let n = self.order as usize;
+        let one = Complex::new(T::one(), T::zero());
+        let mut powers = vec![one.clone()];
+        for m in 1..n {
+            let power = &powers[m - 1] * &d[0];
+            powers.push(power);
+        }
+        let mut h = vec![one];
+        for k in 1..=n {
+            let sum = (0..k).fold(Complex::new(T::zero(), T::zero()), |acc, j| {
+                let binomial = T::from_f64(binomial(k, j) as f64);
+                acc + (&h[j] * &powers[k - j - 1] * &d[k - j]).scale(binomial)
+            });
+            h.push(-sum);
+        }
+        z + (&d[0] * &h[n - 1] / &h[n]).scale(T::from_f64(n as f64))
+    }
+
+    fn glsl_step(&self) -> String {
+        let n = self.order as usize;
+        let mut result = String::new();
+        result.push_str(format!("CX powers[{}];\n", n).as_str());
+        result.push_str("\tpowers[0] = cx_from(vec2(1.0, 0.0));\n");
+        for m in 1..n {
+            result
+                .push_str(format!("\tpowers[{}] = cx_mul(powers[{}], d[0]);\n", m, m - 1).as_str());
+        }
+        result.push_str(format!("\tCX h[{}];\n", n + 1).as_str());
+        result.push_str("\th[0] = cx_from(vec2(1.0, 0.0));\n");
+        for k in 1..=n {
+            let sum = (0..k)
+                .map(|j| {
+                    format!(
+                        "cx_scale(cx_mul(cx_mul(h[{}], powers[{}]), d[{}]), {:.1})",
+                        j,
+                        k - j - 1,
+                        k - j,
+                        binomial(k, j)
+                    )
+                })
+                .reduce(|sum, term| format!("cx_add({}, {})", sum, term))
+                .unwrap();
+            result.push_str(format!("\th[{}] = cx_neg({});\n", k, sum).as_str());
+        }
+        result.push_str(
+            format!(
+                "\tz = cx_add(z, cx_scale(cx_div(cx_mul(d[0], h[{}]), h[{}]), {:.1}));",
+                n - 1,
+                n,
+                n as f32
+            )
+            .as_str(),
+        );
+        result
+    }
+}
+
+// the selectable methods, what fractals, scenes and shader keys store
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Method {
+    #[default]
+    Newton,
+    Halley,
+    Schroeder,
+    Householder(Householder),
+    Chebyshev,
+}
+
+impl Method {
+    pub const ALL: [Method; 5] = [
+        Method::Newton,
+        Method::Halley,
+        Method::Schroeder,
+        Method::Householder(Householder { order: 3 }),
+        Method::Chebyshev,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Method::Newton => "newton",
+            Method::Halley => "halley",
+            Method::Schroeder => "schroeder",
+            Method::Householder(_) => "householder",
+            Method::Chebyshev => "chebyshev",
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Method::Householder(householder) => {
+                (1..=MAX_HOUSEHOLDER_ORDER).contains(&householder.order)
+            }
+            _ => true,
+        }
+    }
+
+    fn method(&self) -> &dyn IterationMethod {
+        match self {
+            Method::Newton => &Newton,
+            Method::Halley => &Halley,
+            Method::Schroeder => &Schroeder,
+            Method::Householder(householder) => householder,
+            Method::Chebyshev => &Chebyshev,
+        }
+    }
+}
+
+impl IterationMethod for Method {
+    fn derivatives(&self) -> usize {
+        self.method().derivatives()
+    }
+
+    fn step<T: Real>(&self, z: Complex<T>, d: &[Complex<T>]) -> Complex<T> {
+        match self {
+            Method::Newton => Newton.step(z, d),
+            Method::Halley => Halley.step(z, d),
+            Method::Schroeder => Schroeder.step(z, d),
+            Method::Householder(householder) => householder.step(z, d),
+            Method::Chebyshev => Chebyshev.step(z, d),
+        }
+    }
+
+    fn glsl_step(&self) -> String {
+        self.method().glsl_step()
+    }
+}
+
+// "householder:<order>" for householder, the plain name for every other method
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Method::Householder(householder) => write!(f, "{}:{}", self.name(), householder.order),
+            method => write!(f, "{}", method.name()),
+        }
+    }
+}
+
+impl FromStr for Method {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (name, order) = match text.split_once(':') {
+            Some((name, order)) => (name, Some(order)),
+            None => (text, None),
+        };
+        let mut method = Method::ALL
+            .into_iter()
+            .find(|method| method.name() == name)
+            .ok_or_else(|| format!("unknown iteration method '{}'", name))?;
+        match (&mut method, order) {
+            (Method::Householder(householder), Some(order)) => {
+                householder.order = order
+                    .parse()
+                    .map_err(|_| format!("invalid householder order '{}'", order))?;
+            }
+            (_, None) => {}
+            (_, Some(_)) => return Err(format!("{} has no order", name)),
+        }
+        if !method.is_valid() {
+            return Err(format!(
+                "householder order must be between 1 and {}",
+                MAX_HOUSEHOLDER_ORDER
+            ));
+        }
+        Ok(method)
+    }
+}
@@ -1,25 +1,69 @@
-use macroquad::{material::Material, math::Vec2, miniquad::error};
+use macroquad::{
+    material::Material,
+    math::{vec2, Vec2},
+    time::get_time,
+};
 use num_complex::Complex;
 
-use crate::{FractalShader, Polynomial};
+use std::path::Path;
+
+use crate::{
+    error::{check_degree, check_roots},
+    CpuRenderer, Cycle, FractalError, FractalShader, Method, Nova, Polynomial, Scene, SceneError,
+    ShaderCache, ShaderCacheStats, ShaderKey, Shading, Viewport, DEFAULT_CONVERGENCE_RADIUS,
+    DEFAULT_TOLERANCE,
+};
+
+// seconds the cycle parameters have to stay unchanged before searching again, so dragging a
+// root or a slider doesn't run the search on every frame
+const CYCLE_IDLE_DELAY: f64 = 0.25;
+
+// everything the detected cycles depend on, they're only searched again when it changes
+#[derive(Clone, PartialEq)]
+struct CycleKey {
+    roots: Vec<Vec2>,
+    method: Method,
+    relaxation: Vec2,
+    nova: Nova,
+    tolerance: f32,
+    convergence_radius: f32,
+    max_iterations: u32,
+    max_cycle_period: u32,
+}
 
 pub struct NewtonFractal {
     max_iterations: u32,
     roots: Vec<Vec2>,
     colors: Vec<[f32; 3]>,
-    derivative: Polynomial,
-    material: FractalShader,
-    real_range: Vec2,
-    imag_range: Vec2,
+    polynomial: Polynomial,
+    method: Method,
+    relaxation: Vec2,
+    nova: Nova,
+    nova_constant: Vec2,
+    tolerance: f32,
+    shading: Shading,
+    gamma: f32,
+    smooth: bool,
+    convergence_radius: f32,
+    no_basin_color: [f32; 3],
+    max_cycle_period: u32,
+    cycles: Vec<Cycle>,
+    cycle_key: Option<CycleKey>,
+    // the parameters the next search waits for and when they last changed
+    pending_cycle_key: Option<(CycleKey, f64)>,
+    // holds the shader being drawn with
+    shaders: ShaderCache,
+    viewport: Viewport,
 }
 
 impl NewtonFractal {
+    // expanded in f64 so many roots don't pile up rounding errors, the shader takes f32
     fn polynomial_from_roots(roots: &[Vec2]) -> Polynomial {
-        let mut poly = Polynomial::new(vec![Complex::new(1.0, 0.0)]);
-        for root in roots {
-            poly.add_root(Complex::new(root.x, root.y));
-        }
-        poly
+        let roots: Vec<Complex<f64>> = roots
+            .iter()
+            .map(|root| Complex::new(root.x as f64, root.y as f64))
+            .collect();
+        Polynomial::<f64>::from_roots(&roots).cast()
     }
 
     pub fn new(
@@ -28,93 +72,393 @@ impl NewtonFractal {
         max_iterations: u32,
         real_range: Vec2,
         imag_range: Vec2,
-    ) -> Option<Self> {
-        if roots.len() != colors.len() {
-            return None;
-        }
+    ) -> Result<Self, FractalError> {
+        check_roots(roots.len(), colors.len())?;
+        check_degree(roots.len())?;
         let polynomial = NewtonFractal::polynomial_from_roots(&roots);
-        let derivative = polynomial.derivative();
-        let mut material = FractalShader::new(roots.len())?;
+        let mut shaders: ShaderCache = ShaderCache::default();
+        let material = shaders.get(ShaderKey::default())?;
         material.set_material_roots(&roots, &colors);
-        material.set_material_derivative_coeff(derivative.get_coefficients());
+        material.set_material_coefficients(polynomial.get_coefficients());
         material.set_material_max_iter(max_iterations);
+        material.set_material_relaxation(vec2(1.0, 0.0));
+        material.set_material_nova_constant(vec2(0.0, 0.0));
+        material.set_material_tolerance(DEFAULT_TOLERANCE);
+        material.set_material_gamma(1.0);
+        material.set_material_no_basin(DEFAULT_CONVERGENCE_RADIUS, [0.0, 0.0, 0.0]);
 
-        Some(Self {
+        Ok(Self {
             roots,
             colors,
-            derivative,
-            material,
+            polynomial,
+            method: Method::default(),
+            relaxation: vec2(1.0, 0.0),
+            nova: Nova::default(),
+            nova_constant: vec2(0.0, 0.0),
+            tolerance: DEFAULT_TOLERANCE,
+            shading: Shading::default(),
+            gamma: 1.0,
+            smooth: false,
+            convergence_radius: DEFAULT_CONVERGENCE_RADIUS,
+            no_basin_color: [0.0, 0.0, 0.0],
+            max_cycle_period: 0,
+            cycles: Vec::new(),
+            cycle_key: None,
+            pending_cycle_key: None,
+            shaders,
             max_iterations,
-            real_range,
-            imag_range,
+            viewport: Viewport::from_ranges(real_range, imag_range, vec2(1.0, 1.0)),
         })
     }
 
-    pub fn get_material(&self) -> &Material {
-        self.material.get_material()
+    pub fn from_scene(scene: &Scene) -> Result<Self, SceneError> {
+        scene.validate()?;
+        let mut fractal = Self::new(
+            scene.get_roots(),
+            scene.colors.clone(),
+            scene.max_iterations,
+            scene.get_real_range(),
+            scene.get_imag_range(),
+        )?;
+        fractal.method = scene.method;
+        fractal.relaxation = scene.get_relaxation();
+        fractal.nova = scene.nova;
+        fractal.nova_constant = scene.get_nova_constant();
+        fractal.tolerance = scene.tolerance;
+        fractal.shading = scene.shading;
+        fractal.gamma = scene.gamma;
+        fractal.smooth = scene.smooth;
+        fractal.convergence_radius = scene.convergence_radius;
+        fractal.no_basin_color = scene.no_basin_color;
+        fractal.max_cycle_period = scene.max_cycle_period;
+        fractal.viewport = scene.viewport(fractal.viewport.get_size());
+        fractal.update()?;
+        Ok(fractal)
     }
 
-    pub fn add_root(&mut self, root: Vec2, color: [f32; 3]) {
-        let material = FractalShader::new(self.roots.len() + 1);
-        if material.is_none() {
-            error!("Failed to create material");
-            return;
+    pub fn scene(&self) -> Scene {
+        Scene {
+            method: self.method,
+            relaxation: self.relaxation.to_array(),
+            nova: self.nova,
+            nova_constant: self.nova_constant.to_array(),
+            tolerance: self.tolerance,
+            shading: self.shading,
+            gamma: self.gamma,
+            smooth: self.smooth,
+            convergence_radius: self.convergence_radius,
+            no_basin_color: self.no_basin_color,
+            max_cycle_period: self.max_cycle_period,
+            center: [
+                self.viewport.get_precise_center().re.clone(),
+                self.viewport.get_precise_center().im.clone(),
+            ],
+            aspect: self.viewport.get_aspect(),
+            rotation: self.viewport.get_rotation(),
+            arbitrary_precision: self.viewport.get_arbitrary_precision(),
+            ..Scene::new(
+                &self.roots,
+                &self.colors,
+                self.max_iterations,
+                self.viewport.get_center(),
+                self.viewport.get_scale(),
+            )
         }
-        self.material = material.unwrap();
+    }
+
+    pub fn set_scene(&mut self, scene: &Scene) -> Result<(), SceneError> {
+        scene.validate()?;
+        check_degree(scene.roots.len())?;
+        self.roots = scene.get_roots();
+        self.colors = scene.colors.clone();
+        self.max_iterations = scene.max_iterations;
+        self.method = scene.method;
+        self.relaxation = scene.get_relaxation();
+        self.nova = scene.nova;
+        self.nova_constant = scene.get_nova_constant();
+        self.tolerance = scene.tolerance;
+        self.shading = scene.shading;
+        self.gamma = scene.gamma;
+        self.smooth = scene.smooth;
+        self.convergence_radius = scene.convergence_radius;
+        self.no_basin_color = scene.no_basin_color;
+        self.max_cycle_period = scene.max_cycle_period;
+        self.viewport = scene.viewport(self.viewport.get_size());
+        Ok(self.update()?)
+    }
+
+    pub fn load_scene<P: AsRef<Path>>(&mut self, path: P) -> Result<(), SceneError> {
+        self.set_scene(&Scene::load(path)?)
+    }
+
+    pub fn save_scene<P: AsRef<Path>>(&self, path: P) -> Result<(), SceneError> {
+        self.scene().save(path)
+    }
+
+    pub fn shader_key(&self) -> ShaderKey {
+        ShaderKey {
+            method: self.method,
+            nova: self.nova,
+            shading: self.shading,
+            smooth: self.smooth,
+            // switches on by itself once zoomed past f32
+            deep: self.viewport.needs_deep_zoom(),
+            ..ShaderKey::default()
+        }
+    }
+
+    pub fn shader_cache(&mut self) -> &mut ShaderCache {
+        &mut self.shaders
+    }
+
+    pub fn shader_cache_stats(&self) -> ShaderCacheStats {
+        self.shaders.stats()
+    }
+
+    pub fn get_material(&self) -> &Material {
+        self.material().get_material()
+    }
+
+    // the shader being drawn with, the cache never evicts it
+    fn material(&self) -> &FractalShader {
+        self.shaders
+            .get_active()
+            .expect("the first shader is compiled on creation")
+    }
+
+    pub fn add_root(&mut self, root: Vec2, color: [f32; 3]) -> Result<(), FractalError> {
+        check_degree(self.roots.len() + 1)?;
         self.roots.push(root);
         self.colors.push(color);
-        self.update();
+        self.update()
     }
 
-    pub fn remove_root(&mut self, index: usize) {
-        let material = FractalShader::new(self.roots.len() - 1);
-        if material.is_none() {
-            error!("Failed to create material");
-            return;
-        }
-        self.material = material.unwrap();
+    pub fn remove_root(&mut self, index: usize) -> Result<(), FractalError> {
+        check_roots(self.roots.len() - 1, self.colors.len() - 1)?;
         self.roots.remove(index);
         self.colors.remove(index);
-        self.update();
+        self.update()
     }
 
     pub fn set_max_iterations(&mut self, max_iterations: u32) {
         self.max_iterations = max_iterations;
     }
 
-    pub fn get_roots(&mut self) -> &mut [Vec2] {
+    pub fn get_max_iterations(&self) -> u32 {
+        self.max_iterations
+    }
+
+    pub fn set_method(&mut self, method: Method) {
+        self.method = method;
+    }
+
+    pub fn get_method(&self) -> Method {
+        self.method
+    }
+
+    pub fn set_relaxation(&mut self, relaxation: Vec2) {
+        self.relaxation = relaxation;
+    }
+
+    pub fn get_relaxation(&self) -> Vec2 {
+        self.relaxation
+    }
+
+    pub fn get_relaxation_mut(&mut self) -> &mut Vec2 {
+        &mut self.relaxation
+    }
+
+    pub fn set_nova(&mut self, nova: Nova) {
+        self.nova = nova;
+    }
+
+    pub fn get_nova(&self) -> Nova {
+        self.nova
+    }
+
+    pub fn get_nova_constant(&self) -> Vec2 {
+        self.nova_constant
+    }
+
+    pub fn get_nova_constant_mut(&mut self) -> &mut Vec2 {
+        &mut self.nova_constant
+    }
+
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+    }
+
+    pub fn get_tolerance(&self) -> f32 {
+        self.tolerance
+    }
+
+    pub fn get_tolerance_mut(&mut self) -> &mut f32 {
+        &mut self.tolerance
+    }
+
+    pub fn set_shading(&mut self, shading: Shading) {
+        self.shading = shading;
+    }
+
+    pub fn get_shading(&self) -> Shading {
+        self.shading
+    }
+
+    pub fn get_gamma(&self) -> f32 {
+        self.gamma
+    }
+
+    pub fn get_gamma_mut(&mut self) -> &mut f32 {
+        &mut self.gamma
+    }
+
+    pub fn set_smooth(&mut self, smooth: bool) {
+        self.smooth = smooth;
+    }
+
+    pub fn get_smooth(&self) -> bool {
+        self.smooth
+    }
+
+    pub fn get_smooth_mut(&mut self) -> &mut bool {
+        &mut self.smooth
+    }
+
+    pub fn set_convergence_radius(&mut self, convergence_radius: f32) {
+        self.convergence_radius = convergence_radius;
+    }
+
+    pub fn get_convergence_radius(&self) -> f32 {
+        self.convergence_radius
+    }
+
+    pub fn get_convergence_radius_mut(&mut self) -> &mut f32 {
+        &mut self.convergence_radius
+    }
+
+    pub fn get_no_basin_color(&self) -> [f32; 3] {
+        self.no_basin_color
+    }
+
+    pub fn get_no_basin_color_mut(&mut self) -> &mut [f32; 3] {
+        &mut self.no_basin_color
+    }
+
+    pub fn set_max_cycle_period(&mut self, max_period: u32) {
+        self.max_cycle_period = max_period;
+    }
+
+    pub fn get_max_cycle_period(&self) -> u32 {
+        self.max_cycle_period
+    }
+
+    pub fn get_max_cycle_period_mut(&mut self) -> &mut u32 {
+        &mut self.max_cycle_period
+    }
+
+    // the cycles found by the last search, which waits for changes to settle
+    pub fn get_cycles(&self) -> &[Cycle] {
+        &self.cycles
+    }
+
+    fn cycle_key(&self) -> CycleKey {
+        CycleKey {
+            roots: self.roots.clone(),
+            method: self.method,
+            relaxation: self.relaxation,
+            nova: self.nova,
+            tolerance: self.tolerance,
+            convergence_radius: self.convergence_radius,
+            max_iterations: self.max_iterations,
+            max_cycle_period: self.max_cycle_period,
+        }
+    }
+
+    pub fn get_roots(&self) -> &[Vec2] {
+        &self.roots
+    }
+
+    pub fn get_roots_mut(&mut self) -> &mut [Vec2] {
         &mut self.roots
     }
 
-    pub fn get_colors(&mut self) -> &mut [[f32; 3]] {
+    pub fn get_colors(&self) -> &[[f32; 3]] {
+        &self.colors
+    }
+
+    pub fn get_colors_mut(&mut self) -> &mut [[f32; 3]] {
         &mut self.colors
     }
 
-    pub fn get_real_range(&self) -> Vec2 {
-        self.real_range
+    pub fn get_viewport(&self) -> &Viewport {
+        &self.viewport
     }
 
-    pub fn get_imag_range(&self) -> Vec2 {
-        self.imag_range
+    pub fn get_viewport_mut(&mut self) -> &mut Viewport {
+        &mut self.viewport
     }
 
-    pub fn get_real_range_mut(&mut self) -> &mut Vec2 {
-        &mut self.real_range
+    // ranges of the unrotated view at the current screen size
+    pub fn get_real_range(&self) -> Vec2 {
+        self.viewport.real_range()
     }
 
-    pub fn get_imag_range_mut(&mut self) -> &mut Vec2 {
-        &mut self.imag_range
+    pub fn get_imag_range(&self) -> Vec2 {
+        self.viewport.imag_range()
     }
 
-    pub fn update(&mut self) {
-        let polynomial = NewtonFractal::polynomial_from_roots(&self.roots);
-        self.derivative = polynomial.derivative();
-        self.material
-            .set_material_range(self.real_range, self.imag_range);
-        self.material.set_material_roots(&self.roots, &self.colors);
-        self.material
-            .set_material_derivative_coeff(self.derivative.get_coefficients());
-        self.material.set_material_max_iter(self.max_iterations);
+    // the search runs on the CPU, so it's skipped while nothing it depends on changes and
+    // waits for changes to settle, the previous cycles are drawn in the meantime
+    fn update_cycles(&mut self) {
+        let cycle_key = self.cycle_key();
+        if self.cycle_key.as_ref() == Some(&cycle_key) {
+            self.pending_cycle_key = None;
+            return;
+        }
+        let now = get_time();
+        let settled = match &self.pending_cycle_key {
+            Some((pending, changed_at)) if *pending == cycle_key => {
+                now - changed_at >= CYCLE_IDLE_DELAY
+            }
+            _ => {
+                self.pending_cycle_key = Some((cycle_key.clone(), now));
+                false
+            }
+        };
+        if settled || self.cycle_key.is_none() {
+            self.cycles = CpuRenderer::from_fractal(self).get_cycles().to_vec();
+            self.cycle_key = Some(cycle_key);
+            self.pending_cycle_key = None;
+        }
+    }
+
+    // on shader errors the previous shader keeps being used with the new parameters
+    pub fn update(&mut self) -> Result<(), FractalError> {
+        // switch to the shader matching the current code generation options
+        let mut result = Ok(());
+        let key = self.shader_key();
+        if self.shaders.get_active_key() != Some(key) {
+            if let Err(err) = self.shaders.get(key) {
+                result = Err(err);
+            }
+        }
+        self.polynomial = NewtonFractal::polynomial_from_roots(&self.roots);
+        self.update_cycles();
+        let material = self
+            .shaders
+            .get_active_mut()
+            .expect("the first shader is compiled on creation");
+        material.set_material_viewport(&self.viewport);
+        material.set_material_roots(&self.roots, &self.colors);
+        material.set_material_coefficients(self.polynomial.get_coefficients());
+        material.set_material_max_iter(self.max_iterations);
+        material.set_material_relaxation(self.relaxation);
+        material.set_material_nova_constant(self.nova_constant);
+        material.set_material_tolerance(self.tolerance);
+        material.set_material_gamma(self.gamma);
+        material.set_material_no_basin(self.convergence_radius, self.no_basin_color);
+        material.set_material_cycles(&self.cycles);
+        result
     }
 
     pub fn len(&self) -> usize {
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+// orbits leaving this radius are considered escaped
+pub const NOVA_BAILOUT: f32 = 1.0e4;
+
+// nova adds a constant c after every step: z - a p(z) / p'(z) + c
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Nova {
+    #[default]
+    Off,
+    // the pixel is z0 and c is the nova constant
+    Julia,
+    // the pixel is c and z0 is the first root, just a conventional start shared by every
+    // method, it's only a critical point of the map for plain newton with a relaxation of 1
+    Mandelbrot,
+}
+
+impl Nova {
+    pub const ALL: [Nova; 3] = [Nova::Off, Nova::Julia, Nova::Mandelbrot];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Nova::Off => "off",
+            Nova::Julia => "julia",
+            Nova::Mandelbrot => "mandelbrot",
+        }
+    }
+}
+
+impl fmt::Display for Nova {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Nova {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Nova::ALL
+            .into_iter()
+            .find(|nova| nova.name() == name)
+            .ok_or_else(|| format!("unknown nova mode '{}'", name))
+    }
+}
@@ -1,24 +1,28 @@
 use num_complex::Complex;
 use std::{
-    fmt::Debug,
+    fmt::{Debug, Display},
     ops::{Mul, MulAssign},
 };
 
+use crate::Real;
+
+// f32 is what the shader takes, coefficients built from many roots are better off in f64 and
+// deep zooms evaluate in arbitrary precision
 #[derive(Clone)]
-pub struct Polynomial {
-    coefficients: Vec<Complex<f32>>, // coefficients of the polynomial in the form a_n * x^n + ... + a_1 * x + a_0
+pub struct Polynomial<T = f32> {
+    coefficients: Vec<Complex<T>>, // coefficients of the polynomial in the form a_n * x^n + ... + a_1 * x + a_0
 }
 
-impl Default for Polynomial {
+impl<T: Real> Default for Polynomial<T> {
     fn default() -> Self {
         Self {
-            coefficients: vec![Complex::new(1.0, 0.0)],
+            coefficients: vec![Complex::new(T::one(), T::zero())],
         }
     }
 }
 
-impl Polynomial {
-    pub fn new(coeff: Vec<Complex<f32>>) -> Self {
+impl<T: Real> Polynomial<T> {
+    pub fn new(coeff: Vec<Complex<T>>) -> Self {
         if coeff.is_empty() {
             panic!("A polynomial must have at least one coefficient");
         }
@@ -27,25 +31,33 @@ impl Polynomial {
         }
     }
 
-    pub fn evaluate(&self, x: Complex<f32>) -> Complex<f32> {
-        let degree = self.degree();
+    // the monic polynomial with these roots
+    pub fn from_roots(roots: &[Complex<T>]) -> Self {
+        let mut poly = Self::default();
+        for root in roots {
+            poly.add_root(root.clone());
+        }
+        poly
+    }
+
+    // horner's scheme
+    pub fn evaluate(&self, x: Complex<T>) -> Complex<T> {
         self.coefficients
             .iter()
-            .enumerate()
-            .fold(Complex::new(0.0, 0.0), |acc, (i, root)| {
-                acc + root * x.powi((degree - i) as i32)
+            .fold(Complex::new(T::zero(), T::zero()), |acc, coeff| {
+                acc * &x + coeff
             })
     }
 
     pub fn derivative(&self) -> Self {
         if self.coefficients.len() == 1 {
-            Self::new(vec![Complex::new(0.0, 0.0)])
+            Self::new(vec![Complex::new(T::zero(), T::zero())])
         } else {
             let mut coeff = Vec::new();
             let len = self.coefficients.len();
             coeff.reserve(len);
             for i in (1..len).rev() {
-                coeff.push(self.coefficients[len - 1 - i] * Complex::new(i as f32, 0.0));
+                coeff.push(&self.coefficients[len - 1 - i] * T::from_f64(i as f64));
             }
             Self {
                 coefficients: coeff,
@@ -57,27 +69,55 @@ impl Polynomial {
         self.coefficients.len() - 1
     }
 
-    pub fn add_root(&mut self, root: Complex<f32>) {
-        let root_poly = Polynomial::new(vec![Complex::new(1.0, 0.0), -root]);
+    pub fn add_root(&mut self, root: Complex<T>) {
+        let root_poly = Polynomial::new(vec![Complex::new(T::one(), T::zero()), -root]);
         *self *= root_poly;
     }
 
-    pub fn add_roots(&mut self, roots: &Vec<Complex<f32>>) {
+    pub fn add_roots(&mut self, roots: &Vec<Complex<T>>) {
         for root in roots {
-            self.add_root(*root);
+            self.add_root(root.clone());
         }
     }
 
-    pub fn get_coefficients(&self) -> &Vec<Complex<f32>> {
+    pub fn get_coefficients(&self) -> &Vec<Complex<T>> {
         &self.coefficients
     }
+
+    // the same polynomial in another precision, rounding when narrowing
+    pub fn cast<U: Real>(&self) -> Polynomial<U> {
+        Polynomial {
+            coefficients: self
+                .coefficients
+                .iter()
+                .map(|coeff| {
+                    Complex::new(
+                        U::from_f64(coeff.re.to_f64()),
+                        U::from_f64(coeff.im.to_f64()),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&Polynomial<f32>> for Polynomial<f64> {
+    fn from(poly: &Polynomial<f32>) -> Self {
+        poly.cast()
+    }
+}
+
+impl From<&Polynomial<f64>> for Polynomial<f32> {
+    fn from(poly: &Polynomial<f64>) -> Self {
+        poly.cast()
+    }
 }
 
-impl From<(Vec<f32>, Vec<f32>)> for Polynomial {
-    fn from((re_root, im_root): (Vec<f32>, Vec<f32>)) -> Self {
+impl<T: Real> From<(Vec<T>, Vec<T>)> for Polynomial<T> {
+    fn from((re_root, im_root): (Vec<T>, Vec<T>)) -> Self {
         let mut roots = Vec::new();
         for i in 0..re_root.len() {
-            roots.push(Complex::new(re_root[i], im_root[i]));
+            roots.push(Complex::new(re_root[i].clone(), im_root[i].clone()));
         }
         Self {
             coefficients: roots,
@@ -85,15 +125,17 @@ impl From<(Vec<f32>, Vec<f32>)> for Polynomial {
     }
 }
 
-impl Mul for Polynomial {
+impl<T: Real> Mul for Polynomial<T> {
     type Output = Self;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        let mut coeff =
-            vec![Complex::new(0.0, 0.0); self.coefficients.len() + rhs.coefficients.len() - 1];
+        let mut coeff = vec![
+            Complex::new(T::zero(), T::zero());
+            self.coefficients.len() + rhs.coefficients.len() - 1
+        ];
         for i in 0..self.coefficients.len() {
             for j in 0..rhs.coefficients.len() {
-                coeff[i + j] += self.coefficients[i] * rhs.coefficients[j];
+                coeff[i + j] = coeff[i + j].clone() + &self.coefficients[i] * &rhs.coefficients[j];
             }
         }
         Self {
@@ -102,19 +144,19 @@ impl Mul for Polynomial {
     }
 }
 
-impl MulAssign for Polynomial {
+impl<T: Real> MulAssign for Polynomial<T> {
     fn mul_assign(&mut self, rhs: Self) {
         *self = self.clone() * rhs;
     }
 }
 
-impl PartialEq for Polynomial {
+impl<T: Real> PartialEq for Polynomial<T> {
     fn eq(&self, other: &Self) -> bool {
         self.coefficients == other.coefficients
     }
 }
 
-impl Debug for Polynomial {
+impl<T: Real + PartialOrd + Display> Debug for Polynomial<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.coefficients
             .iter()
@@ -122,7 +164,7 @@ impl Debug for Polynomial {
             .try_for_each(|(i, coeff)| {
                 if self.coefficients.len() - 1 - i == 0 {
                     write!(f, "{}", coeff)?
-                } else if coeff.re == 0.0 && coeff.im == 0.0 {
+                } else if coeff.re.is_zero() && coeff.im.is_zero() {
                     return Ok(()) as std::fmt::Result;
                 } else {
                     write!(f, "{} * x^{} + ", coeff, self.coefficients.len() - 1 - i)?
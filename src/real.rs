@@ -0,0 +1,291 @@
+use dashu_float::{round::mode::HalfEven, FBig};
+use num_traits::{Num, One, Zero};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    f64::consts::{LOG10_2, LOG2_10},
+    fmt,
+    ops::{Add, Div, Mul, Neg, Rem, Sub},
+    str::FromStr,
+};
+
+// as many as an f64
+const F64_BITS: usize = 53;
+// decimal digits telling every f64 apart
+const F64_DIGITS: usize = 17;
+
+// the real numbers polynomials and orbits are computed in, from f32 up to arbitrary precision
+pub trait Real: Clone + Num + Neg<Output = Self> {
+    // what polynomials are expanded and evaluated in, f32 coefficients of many roots are too
+    // coarse
+    type Wide: Real;
+
+    fn from_f64(value: f64) -> Self;
+
+    fn widen(&self) -> Self::Wide;
+
+    fn narrow(wide: Self::Wide) -> Self;
+
+    // the closest f64, which is all coloring needs
+    fn to_f64(&self) -> f64;
+
+    // rounds to `bits` of mantissa, which results computed from it keep, fixed size floats
+    // stay as they are
+    fn with_precision(self, _bits: usize) -> Self {
+        self
+    }
+}
+
+impl Real for f32 {
+    type Wide = f64;
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn widen(&self) -> f64 {
+        *self as f64
+    }
+
+    fn narrow(wide: f64) -> Self {
+        wide as f32
+    }
+
+    fn to_f64(&self) -> f64 {
+        *self as f64
+    }
+}
+
+impl Real for f64 {
+    type Wide = f64;
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn widen(&self) -> f64 {
+        *self
+    }
+
+    fn narrow(wide: f64) -> Self {
+        wide
+    }
+
+    fn to_f64(&self) -> f64 {
+        *self
+    }
+}
+
+// an arbitrary precision float, the result of an operation has the larger precision of its
+// operands and exact values take the precision of what they meet, NaN stands in for what FBig
+// would panic on like dividing by zero
+#[derive(Clone, Debug, PartialEq)]
+pub struct BigFloat(Option<FBig<HalfEven>>);
+
+impl BigFloat {
+    pub const NAN: BigFloat = BigFloat(None);
+
+    pub fn is_nan(&self) -> bool {
+        self.0.is_none()
+    }
+
+    // bits of mantissa, 0 when exact
+    pub fn precision(&self) -> usize {
+        self.0.as_ref().map_or(0, FBig::precision)
+    }
+
+    // a quotient of exact values usually isn't one, it's rounded like an f64
+    fn divisible(lhs: FBig<HalfEven>, rhs: &FBig<HalfEven>) -> Option<FBig<HalfEven>> {
+        if rhs.repr().is_zero() {
+            None
+        } else if lhs.precision() == 0 && rhs.precision() == 0 {
+            Some(lhs.with_precision(F64_BITS).value())
+        } else {
+            Some(lhs)
+        }
+    }
+
+    fn zip(self, rhs: Self, op: impl FnOnce(FBig<HalfEven>, FBig<HalfEven>) -> Self) -> Self {
+        match (self.0, rhs.0) {
+            (Some(lhs), Some(rhs)) => op(lhs, rhs),
+            _ => Self::NAN,
+        }
+    }
+}
+
+impl Real for BigFloat {
+    type Wide = BigFloat;
+
+    // exact, infinities can't be operated on either
+    fn from_f64(value: f64) -> Self {
+        if !value.is_finite() {
+            return Self::NAN;
+        }
+        BigFloat(
+            FBig::try_from(value)
+                .ok()
+                .map(|value| value.with_precision(0).value()),
+        )
+    }
+
+    fn widen(&self) -> Self {
+        self.clone()
+    }
+
+    fn narrow(wide: Self) -> Self {
+        wide
+    }
+
+    fn to_f64(&self) -> f64 {
+        self.0
+            .as_ref()
+            .map_or(f64::NAN, |value| value.to_f64().value())
+    }
+
+    fn with_precision(self, bits: usize) -> Self {
+        BigFloat(self.0.map(|value| value.with_precision(bits).value()))
+    }
+}
+
+impl Add for BigFloat {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        self.zip(rhs, |lhs, rhs| BigFloat(Some(lhs + rhs)))
+    }
+}
+
+impl Sub for BigFloat {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.zip(rhs, |lhs, rhs| BigFloat(Some(lhs - rhs)))
+    }
+}
+
+impl Mul for BigFloat {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        self.zip(rhs, |lhs, rhs| BigFloat(Some(lhs * rhs)))
+    }
+}
+
+impl Div for BigFloat {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        self.zip(rhs, |lhs, rhs| {
+            BigFloat(Self::divisible(lhs, &rhs).map(|lhs| lhs / rhs))
+        })
+    }
+}
+
+impl Rem for BigFloat {
+    type Output = Self;
+
+    fn rem(self, rhs: Self) -> Self {
+        self.zip(rhs, |lhs, rhs| {
+            BigFloat(Self::divisible(lhs, &rhs).map(|lhs| lhs % rhs))
+        })
+    }
+}
+
+impl Neg for BigFloat {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        BigFloat(self.0.map(|value| -value))
+    }
+}
+
+impl Zero for BigFloat {
+    fn zero() -> Self {
+        BigFloat(Some(FBig::ZERO))
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0
+            .as_ref()
+            .map_or(false, |value| value.repr().is_zero())
+    }
+}
+
+impl One for BigFloat {
+    fn one() -> Self {
+        BigFloat(Some(FBig::ONE))
+    }
+}
+
+// the shortest decimal reading back the same value, f64 values come out as f64 prints them,
+// beyond as many digits as the value has bits the rest is only rounding
+impl fmt::Display for BigFloat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(value) = &self.0 else {
+            return write!(f, "NaN");
+        };
+        let bits = value.repr().digits();
+        let mut digits = ((bits as f64 * LOG10_2).floor() as usize).clamp(1, F64_DIGITS);
+        loop {
+            let decimal: FBig<HalfEven, 10> = value.clone().with_base_and_precision(digits).value();
+            let text = decimal.to_string();
+            if digits >= bits.max(F64_DIGITS) || text.parse().ok().as_ref() == Some(self) {
+                return write!(f, "{}", text);
+            }
+            digits += 1;
+        }
+    }
+}
+
+// digits from the first nonzero one on, trailing zeros of an integer don't count
+fn significant_digits(text: &str) -> usize {
+    let mantissa = text.split(['e', 'E']).next().unwrap_or_default();
+    let digits = mantissa.trim_start_matches(['-', '+', '0', '.']);
+    let digits = if mantissa.contains('.') {
+        digits
+    } else {
+        digits.trim_end_matches('0')
+    };
+    digits.chars().filter(char::is_ascii_digit).count()
+}
+
+// decimal like "-0.743643887037158704752191506114774" or "1e-30", every digit is kept and
+// the ones an f64 could hold are rounded like an f64
+impl FromStr for BigFloat {
+    type Err = <FBig<HalfEven> as Num>::FromStrRadixErr;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let decimal = FBig::<HalfEven, 10>::from_str(text)?;
+        let bits = match significant_digits(text) {
+            digits if digits <= F64_DIGITS => F64_BITS,
+            digits => (digits as f64 * LOG2_10).ceil() as usize,
+        };
+        // converting leaves values it can exactly as they are even beyond the bits, and only
+        // lowering the precision rounds, so it's raised first
+        let value: FBig<HalfEven> = decimal.with_base_and_precision(bits).value();
+        let value = value.with_precision(usize::MAX).value();
+        Ok(BigFloat(Some(value.with_precision(bits).value())))
+    }
+}
+
+// as decimal strings, JSON numbers would round to f64
+impl Serialize for BigFloat {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for BigFloat {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+impl Num for BigFloat {
+    type FromStrRadixErr = <FBig<HalfEven> as Num>::FromStrRadixErr;
+
+    fn from_str_radix(text: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+        FBig::from_str_radix(text, radix).map(|value| BigFloat(Some(value)))
+    }
+}
@@ -0,0 +1,906 @@
+use macroquad::{
+    math::{vec2, Vec2},
+    texture::Image,
+};
+use num_complex::Complex;
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use crate::{
+    cycle_color, error::check_roots, BigFloat, Cycle, FractalError, IterationMethod, Method,
+    NewtonFractal, Nova, Polynomial, Real, Shading, Viewport, MAX_CYCLE_POINTS, NOVA_BAILOUT,
+};
+
+// orbits closer than this to a root or moving less in one step stop iterating
+pub const DEFAULT_TOLERANCE: f32 = 1.0e-5;
+// orbits ending further than this from every root don't belong to any basin
+pub const DEFAULT_CONVERGENCE_RADIUS: f32 = 0.1;
+
+// seeds per side of the grid searched for cycles
+const CYCLE_SEEDS: usize = 32;
+// relative distance under which an orbit counts as back to its start
+const CYCLE_TOLERANCE: f64 = 1.0e-3;
+
+// bits beyond the pixel size covering the error orbits pick up on their way to a root
+const GUARD_BITS: usize = 32;
+// arbitrary precision never gets coarser than this many bits, nor finer than the maximum
+const MIN_PRECISION: usize = 64;
+const MAX_PRECISION: usize = 4096;
+
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct TileOptions {
+    pub tile_size: u16,
+    pub threads: usize,
+}
+
+impl Default for TileOptions {
+    fn default() -> Self {
+        Self {
+            tile_size: 64,
+            threads: thread::available_parallelism()
+                .map(|threads| threads.get())
+                .unwrap_or(1),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrbitState {
+    Converged,
+    Escaped,
+    // still moving after the maximum number of iterations
+    Bounded,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Orbit {
+    pub z: Complex<f32>,
+    // the iterate before `z`
+    pub previous: Complex<f32>,
+    pub iterations: u32,
+    pub state: OrbitState,
+}
+
+#[derive(Clone, Copy)]
+struct Tile {
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+}
+
+// the iteration in one precision, coloring only needs the f32 orbit it ends with
+#[derive(Clone)]
+struct Iteration<T: Real> {
+    roots: Vec<Complex<T>>,
+    // derivatives[k] is the (k + 1)-th derivative, as many as the method needs
+    derivatives: Vec<Polynomial<T::Wide>>,
+    relaxation: Complex<T>,
+    nova_constant: Complex<T>,
+}
+
+fn to_single<T: Real>(z: &Complex<T>) -> Complex<f32> {
+    Complex::new(z.re.to_f64() as f32, z.im.to_f64() as f32)
+}
+
+// the difference is taken in the precision of the orbit, its size is fine in f32
+fn distance<T: Real>(a: &Complex<T>, b: &Complex<T>) -> f32 {
+    to_single(&(a - b)).norm()
+}
+
+fn build_tiles(width: u16, height: u16, tile_size: u16) -> Vec<Tile> {
+    let tile_size = tile_size.max(1);
+    let mut tiles = Vec::new();
+    for y in (0..height).step_by(tile_size as usize) {
+        for x in (0..width).step_by(tile_size as usize) {
+            tiles.push(Tile {
+                x,
+                y,
+                width: tile_size.min(width - x),
+                height: tile_size.min(height - y),
+            });
+        }
+    }
+    tiles
+}
+
+// CPU counterpart of the generated fragment shader, usable without a GPU context
+#[derive(Clone)]
+pub struct CpuRenderer {
+    roots: Vec<Complex<f32>>,
+    colors: Vec<[f32; 3]>,
+    // the iteration of the fragment shader in f64, so exports of zooms too deep for f32 don't
+    // come out in blocks
+    double: Iteration<f64>,
+    // the same iteration for extreme zooms, when arbitrary precision is enabled
+    precise: Option<Iteration<BigFloat>>,
+    method: Method,
+    relaxation: Complex<f32>,
+    nova: Nova,
+    nova_constant: Complex<f32>,
+    tolerance: f32,
+    shading: Shading,
+    gamma: f32,
+    smooth: bool,
+    convergence_radius: f32,
+    no_basin_color: [f32; 3],
+    max_cycle_period: u32,
+    cycles: Vec<Cycle>,
+    max_iterations: u32,
+    // the closest f64 to the precise center, for f64 orbits
+    center: Complex<f64>,
+    // arbitrary precision starts from it, so zooms beyond f64 keep their place
+    precise_center: Complex<BigFloat>,
+    // half the span of the view along each axis, before rotating
+    extent: Complex<f64>,
+    // radians the plane is turned by around the center
+    rotation: f32,
+}
+
+impl CpuRenderer {
+    pub fn new(
+        roots: Vec<Vec2>,
+        colors: Vec<[f32; 3]>,
+        max_iterations: u32,
+        real_range: Vec2,
+        imag_range: Vec2,
+    ) -> Result<Self, FractalError> {
+        check_roots(roots.len(), colors.len())?;
+        let roots: Vec<Complex<f32>> = roots
+            .iter()
+            .map(|root| Complex::new(root.x, root.y))
+            .collect();
+
+        let mut renderer = Self {
+            double: Iteration {
+                roots: Vec::new(),
+                derivatives: Vec::new(),
+                relaxation: Complex::new(1.0, 0.0),
+                nova_constant: Complex::new(0.0, 0.0),
+            },
+            precise: None,
+            method: Method::default(),
+            relaxation: Complex::new(1.0, 0.0),
+            nova: Nova::default(),
+            nova_constant: Complex::new(0.0, 0.0),
+            tolerance: DEFAULT_TOLERANCE,
+            shading: Shading::default(),
+            gamma: 1.0,
+            smooth: false,
+            convergence_radius: DEFAULT_CONVERGENCE_RADIUS,
+            no_basin_color: [0.0, 0.0, 0.0],
+            max_cycle_period: 0,
+            cycles: Vec::new(),
+            roots,
+            colors,
+            max_iterations,
+            center: Complex::new(0.0, 0.0),
+            precise_center: Complex::new(BigFloat::from_f64(0.0), BigFloat::from_f64(0.0)),
+            extent: Complex::new(1.0, 1.0),
+            rotation: 0.0,
+        };
+        renderer.set_ranges(real_range, imag_range);
+        renderer.set_method(Method::default());
+        Ok(renderer)
+    }
+
+    pub fn from_fractal(fractal: &NewtonFractal) -> Self {
+        let roots = fractal.get_roots().to_vec();
+        let colors = fractal.get_colors().to_vec();
+        let mut renderer = Self::new(
+            roots,
+            colors,
+            fractal.get_max_iterations(),
+            fractal.get_real_range(),
+            fractal.get_imag_range(),
+        )
+        .expect("NewtonFractal roots are always valid");
+        renderer.set_method(fractal.get_method());
+        let relaxation = fractal.get_relaxation();
+        renderer.set_relaxation(Complex::new(relaxation.x, relaxation.y));
+        let constant = fractal.get_nova_constant();
+        renderer.set_nova(fractal.get_nova(), Complex::new(constant.x, constant.y));
+        renderer.set_tolerance(fractal.get_tolerance());
+        renderer.set_shading(fractal.get_shading(), fractal.get_gamma());
+        renderer.set_smooth(fractal.get_smooth());
+        renderer.set_no_basin(
+            fractal.get_convergence_radius(),
+            fractal.get_no_basin_color(),
+        );
+        renderer.set_max_cycle_period(fractal.get_max_cycle_period());
+        renderer.set_viewport(fractal.get_viewport());
+        renderer
+    }
+
+    // the roots are exact in every precision, arbitrary precision expands the polynomial
+    // exactly too
+    fn iteration<T: Real>(&self) -> Iteration<T> {
+        let complex =
+            |z: Complex<f32>| Complex::new(T::from_f64(z.re as f64), T::from_f64(z.im as f64));
+        let roots: Vec<Complex<T>> = self.roots.iter().map(|&root| complex(root)).collect();
+        let wide: Vec<Complex<T::Wide>> = roots
+            .iter()
+            .map(|root| Complex::new(root.re.widen(), root.im.widen()))
+            .collect();
+        let mut derivatives = Vec::new();
+        let mut derivative = Polynomial::from_roots(&wide).derivative();
+        for _ in 0..self.method.derivatives() {
+            let next = derivative.derivative();
+            derivatives.push(derivative);
+            derivative = next;
+        }
+        Iteration {
+            roots,
+            derivatives,
+            relaxation: complex(self.relaxation),
+            nova_constant: complex(self.nova_constant),
+        }
+    }
+
+    fn update_iteration(&mut self) {
+        self.double = self.iteration();
+        if self.precise.is_some() {
+            self.precise = Some(self.iteration());
+        }
+        self.update_cycles();
+    }
+
+    pub fn set_method(&mut self, method: Method) {
+        self.method = method;
+        self.update_iteration();
+    }
+
+    pub fn get_method(&self) -> Method {
+        self.method
+    }
+
+    // the step of the method is scaled by `relaxation`, 1 leaves the method unchanged
+    pub fn set_relaxation(&mut self, relaxation: Complex<f32>) {
+        self.relaxation = relaxation;
+        self.update_iteration();
+    }
+
+    pub fn get_relaxation(&self) -> Complex<f32> {
+        self.relaxation
+    }
+
+    // `constant` is only used by the julia variant, mandelbrot takes c from the pixel
+    pub fn set_nova(&mut self, nova: Nova, constant: Complex<f32>) {
+        self.nova = nova;
+        self.nova_constant = constant;
+        self.update_iteration();
+    }
+
+    pub fn get_nova(&self) -> Nova {
+        self.nova
+    }
+
+    pub fn get_nova_constant(&self) -> Complex<f32> {
+        self.nova_constant
+    }
+
+    // 0 always runs the maximum number of iterations
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+        self.update_cycles();
+    }
+
+    pub fn get_tolerance(&self) -> f32 {
+        self.tolerance
+    }
+
+    // `gamma` is only used by the gamma shading
+    pub fn set_shading(&mut self, shading: Shading, gamma: f32) {
+        self.shading = shading;
+        self.gamma = gamma;
+    }
+
+    pub fn get_shading(&self) -> Shading {
+        self.shading
+    }
+
+    pub fn get_gamma(&self) -> f32 {
+        self.gamma
+    }
+
+    // shade with fractional iteration counts, only affects basins of roots
+    pub fn set_smooth(&mut self, smooth: bool) {
+        self.smooth = smooth;
+    }
+
+    pub fn get_smooth(&self) -> bool {
+        self.smooth
+    }
+
+    // orbits ending further than `convergence_radius` from every root are painted in `color`
+    pub fn set_no_basin(&mut self, convergence_radius: f32, color: [f32; 3]) {
+        self.convergence_radius = convergence_radius;
+        self.no_basin_color = color;
+        self.update_cycles();
+    }
+
+    pub fn get_convergence_radius(&self) -> f32 {
+        self.convergence_radius
+    }
+
+    pub fn get_no_basin_color(&self) -> [f32; 3] {
+        self.no_basin_color
+    }
+
+    // 0 disables cycle detection, the cycles are searched again whenever the iteration changes
+    pub fn set_max_cycle_period(&mut self, max_period: u32) {
+        self.max_cycle_period = max_period;
+        self.update_cycles();
+    }
+
+    pub fn get_max_cycle_period(&self) -> u32 {
+        self.max_cycle_period
+    }
+
+    pub fn get_cycles(&self) -> &[Cycle] {
+        &self.cycles
+    }
+
+    // nova orbits are colored on their own
+    fn update_cycles(&mut self) {
+        self.cycles = match self.nova {
+            Nova::Off => self.find_cycles(self.max_cycle_period),
+            Nova::Julia | Nova::Mandelbrot => Vec::new(),
+        };
+    }
+
+    // mirrors smoothIterations in the fragment shader
+    pub fn smooth_iterations(&self, orbit: &Orbit, root: Complex<f32>) -> f32 {
+        let current = (orbit.z - root).norm();
+        let last = (orbit.previous - root).norm();
+        if !(current < self.tolerance && current > 0.0 && last > current) {
+            return orbit.iterations as f32;
+        }
+        let fraction = (self.tolerance.ln() - last.ln()) / (current.ln() - last.ln());
+        (orbit.iterations - 1) as f32 + fraction.clamp(0.0, 1.0)
+    }
+
+    fn shade(&self, color: [f32; 3], iterations: f32) -> [f32; 3] {
+        let brightness = self
+            .shading
+            .brightness(iterations, self.max_iterations, self.gamma);
+        color.map(|channel| channel * brightness)
+    }
+
+    pub fn get_roots(&self) -> &[Complex<f32>] {
+        &self.roots
+    }
+
+    pub fn get_colors(&self) -> &[[f32; 3]] {
+        &self.colors
+    }
+
+    pub fn get_max_iterations(&self) -> u32 {
+        self.max_iterations
+    }
+
+    // pixels are iterated in arbitrary precision, as many bits as the view needs
+    pub fn set_arbitrary_precision(&mut self, arbitrary_precision: bool) {
+        self.precise = arbitrary_precision.then(|| self.iteration());
+    }
+
+    pub fn get_arbitrary_precision(&self) -> bool {
+        self.precise.is_some()
+    }
+
+    // bits telling the pixels of a width x height image apart, plus guard bits
+    pub fn precision(&self, width: u32, height: u32) -> usize {
+        let pixel = (2.0 * self.extent.re.abs() / width as f64)
+            .min(2.0 * self.extent.im.abs() / height as f64);
+        let bits = (self.center.norm().max(1.0) / pixel).log2().ceil().max(0.0);
+        (bits as usize + GUARD_BITS).clamp(MIN_PRECISION, MAX_PRECISION)
+    }
+
+    // the ranges round the center to f32 and blur deep zooms
+    pub fn get_real_range(&self) -> Vec2 {
+        vec2(
+            (self.center.re - self.extent.re) as f32,
+            (self.center.re + self.extent.re) as f32,
+        )
+    }
+
+    pub fn get_imag_range(&self) -> Vec2 {
+        vec2(
+            (self.center.im - self.extent.im) as f32,
+            (self.center.im + self.extent.im) as f32,
+        )
+    }
+
+    pub fn set_ranges(&mut self, real_range: Vec2, imag_range: Vec2) {
+        let (real_range, imag_range) = (real_range.as_dvec2(), imag_range.as_dvec2());
+        self.set_view(
+            Complex::new(
+                (real_range.x + real_range.y) / 2.0,
+                (imag_range.x + imag_range.y) / 2.0,
+            ),
+            Complex::new(
+                (real_range.y - real_range.x) / 2.0,
+                (imag_range.y - imag_range.x) / 2.0,
+            ),
+        );
+    }
+
+    // `extent` is half the span of the view along each axis, before rotating
+    pub fn set_view(&mut self, center: Complex<f64>, extent: Complex<f64>) {
+        self.set_precise_view(
+            Complex::new(BigFloat::from_f64(center.re), BigFloat::from_f64(center.im)),
+            extent,
+        );
+    }
+
+    // the same with a center beyond f64
+    pub fn set_precise_view(&mut self, center: Complex<BigFloat>, extent: Complex<f64>) {
+        self.center = Complex::new(center.re.to_f64(), center.im.to_f64());
+        self.precise_center = center;
+        self.extent = extent;
+    }
+
+    pub fn get_center(&self) -> Complex<f64> {
+        self.center
+    }
+
+    pub fn get_precise_center(&self) -> &Complex<BigFloat> {
+        &self.precise_center
+    }
+
+    pub fn get_extent(&self) -> Complex<f64> {
+        self.extent
+    }
+
+    pub fn set_rotation(&mut self, rotation: f32) {
+        self.rotation = rotation;
+    }
+
+    pub fn get_rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    // renders what `viewport` shows, the image should have the size of the viewport
+    pub fn set_viewport(&mut self, viewport: &Viewport) {
+        let extent = viewport.extent();
+        self.set_precise_view(
+            viewport.get_precise_center().clone(),
+            Complex::new(extent.x, extent.y),
+        );
+        self.rotation = viewport.get_rotation();
+    }
+
+    // same mapping as the vertex shader, sampling the center of the pixel, the offset is added
+    // to `center` in its precision
+    fn pixel<T: Real>(
+        &self,
+        center: Complex<T>,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Complex<T> {
+        let u = (x as f64 + 0.5) / width as f64 * 2.0 - 1.0;
+        let v = (y as f64 + 0.5) / height as f64 * 2.0 - 1.0;
+        let offset = Complex::new(u * self.extent.re, v * self.extent.im)
+            * Complex::from_polar(1.0, self.rotation as f64);
+        Complex::new(
+            center.re + T::from_f64(offset.re),
+            center.im + T::from_f64(offset.im),
+        )
+    }
+
+    pub fn pixel_to_complex(&self, x: u32, y: u32, width: u32, height: u32) -> Complex<f64> {
+        self.pixel(self.center, x, y, width, height)
+    }
+
+    // one relaxed step of the method, `d` is scratch space for the derivatives
+    fn step<T: Real>(
+        &self,
+        iteration: &Iteration<T>,
+        z: Complex<T>,
+        d: &mut Vec<Complex<T>>,
+    ) -> Complex<T> {
+        d.clear();
+        d.push(
+            iteration
+                .roots
+                .iter()
+                .fold(Complex::new(T::one(), T::zero()), |acc, root| {
+                    acc * (&z - root)
+                }),
+        );
+        let wide = Complex::new(z.re.widen(), z.im.widen());
+        d.extend(iteration.derivatives.iter().map(|derivative| {
+            let value = derivative.evaluate(wide.clone());
+            Complex::new(T::narrow(value.re), T::narrow(value.im))
+        }));
+        let next = self.method.step(z.clone(), d);
+        &z + &iteration.relaxation * (next - &z)
+    }
+
+    fn converged<T: Real>(
+        &self,
+        iteration: &Iteration<T>,
+        z: &Complex<T>,
+        previous: &Complex<T>,
+    ) -> bool {
+        distance(z, previous) < self.tolerance
+            || iteration
+                .roots
+                .iter()
+                .any(|root| distance(z, root) < self.tolerance)
+    }
+
+    fn orbit_in<T: Real>(&self, iteration: &Iteration<T>, mut z: Complex<T>) -> Orbit {
+        let mut d = Vec::with_capacity(iteration.derivatives.len() + 1);
+        let mut previous = z.clone();
+        for i in 0..self.max_iterations {
+            previous = z;
+            z = self.step(iteration, previous.clone(), &mut d);
+            if self.converged(iteration, &z, &previous) {
+                return Orbit {
+                    z: to_single(&z),
+                    previous: to_single(&previous),
+                    iterations: i + 1,
+                    state: OrbitState::Converged,
+                };
+            }
+        }
+        Orbit {
+            z: to_single(&z),
+            previous: to_single(&previous),
+            iterations: self.max_iterations,
+            state: OrbitState::Bounded,
+        }
+    }
+
+    // mirrors iterate in the fragment shader
+    pub fn orbit(&self, z: Complex<f64>) -> Orbit {
+        self.orbit_in(&self.double, z)
+    }
+
+    pub fn iterate(&self, z: Complex<f64>) -> Complex<f32> {
+        self.orbit(z).z
+    }
+
+    // the mandelbrot variant starts from an exact root, which gets the precision of the pixel
+    fn nova_orbit_in<T: Real>(
+        &self,
+        iteration: &Iteration<T>,
+        pixel: Complex<T>,
+        precision: usize,
+    ) -> Orbit {
+        let (mut z, c) = match self.nova {
+            Nova::Off => (pixel, Complex::new(T::zero(), T::zero())),
+            Nova::Julia => (pixel, iteration.nova_constant.clone()),
+            Nova::Mandelbrot => {
+                let root = &iteration.roots[0];
+                let start = Complex::new(
+                    root.re.clone().with_precision(precision),
+                    root.im.clone().with_precision(precision),
+                );
+                (start, pixel)
+            }
+        };
+        let mut d = Vec::with_capacity(iteration.derivatives.len() + 1);
+        let mut previous = z.clone();
+        for i in 0..self.max_iterations {
+            previous = z;
+            z = self.step(iteration, previous.clone(), &mut d) + &c;
+            let state = if to_single(&z).norm_sqr() > NOVA_BAILOUT * NOVA_BAILOUT {
+                OrbitState::Escaped
+            } else if distance(&z, &previous) < self.tolerance {
+                OrbitState::Converged
+            } else {
+                continue;
+            };
+            return Orbit {
+                z: to_single(&z),
+                previous: to_single(&previous),
+                iterations: i + 1,
+                state,
+            };
+        }
+        Orbit {
+            z: to_single(&z),
+            previous: to_single(&previous),
+            iterations: self.max_iterations,
+            state: OrbitState::Bounded,
+        }
+    }
+
+    // mirrors the nova fragment shader, `pixel` is z0 or c depending on the variant
+    pub fn nova_orbit(&self, pixel: Complex<f64>) -> Orbit {
+        self.nova_orbit_in(&self.double, pixel, 0)
+    }
+
+    // mirrors closestRoot: the first root strictly closer than every following one wins,
+    // the last root is used when no comparison succeeds (ties and NaN included)
+    pub fn closest_root(&self, z: Complex<f32>) -> Option<usize> {
+        if self.roots.is_empty() {
+            return None;
+        }
+        let distances: Vec<f32> = self.roots.iter().map(|root| (z - root).norm()).collect();
+        let len = distances.len();
+        Some(
+            (0..len)
+                .find(|&i| ((i + 1)..len).all(|j| distances[i] < distances[j]))
+                .unwrap_or(len - 1),
+        )
+    }
+
+    // the closest root if the orbit ended within the convergence radius of it
+    pub fn basin(&self, z: Complex<f32>) -> Option<usize> {
+        self.closest_root(z)
+            .filter(|&index| (z - self.roots[index]).norm() <= self.convergence_radius)
+    }
+
+    // the first cycle passing within the convergence radius of z, like the fragment shader
+    pub fn cycle_basin(&self, z: Complex<f32>) -> Option<usize> {
+        self.cycles
+            .iter()
+            .position(|cycle| cycle.contains(z, self.convergence_radius))
+    }
+
+    // derivative of one step by central differences, the step is holomorphic so the
+    // real direction is enough
+    fn step_derivative(&self, z: Complex<f64>, d: &mut Vec<Complex<f64>>) -> Complex<f64> {
+        let h = 1.0e-3 * z.norm().max(1.0);
+        (self.step(&self.double, z + h, d) - self.step(&self.double, z - h, d)) / (2.0 * h)
+    }
+
+    // the attracting cycle through z of period at most `max_period`, if z is on one
+    fn periodic_orbit(
+        &self,
+        z: Complex<f64>,
+        max_period: u32,
+        d: &mut Vec<Complex<f64>>,
+    ) -> Option<Cycle> {
+        let mut points = vec![z];
+        loop {
+            let next = self.step(&self.double, points[points.len() - 1], d);
+            if (next - z).norm() < CYCLE_TOLERANCE * z.norm().max(1.0) {
+                break;
+            }
+            if points.len() >= max_period as usize {
+                return None;
+            }
+            points.push(next);
+        }
+        if points
+            .iter()
+            .any(|point| self.basin(to_single(point)).is_some())
+        {
+            return None;
+        }
+        let multiplier = points.iter().fold(Complex::new(1.0, 0.0), |acc, &point| {
+            acc * self.step_derivative(point, d)
+        });
+        if multiplier.is_nan() || multiplier.norm() >= 1.0 {
+            return None;
+        }
+        let mut points: Vec<Complex<f32>> = points.iter().map(to_single).collect();
+        let first = (0..points.len())
+            .min_by(|&i, &j| points[i].re.total_cmp(&points[j].re))
+            .unwrap_or(0);
+        points.rotate_left(first);
+        Some(Cycle {
+            points,
+            multiplier: to_single(&multiplier),
+        })
+    }
+
+    // follows seeds on a grid around the roots and keeps the cycles they settle on, until
+    // the cycles hold MAX_CYCLE_POINTS points
+    pub fn find_cycles(&self, max_period: u32) -> Vec<Cycle> {
+        let mut cycles: Vec<Cycle> = Vec::new();
+        if self.roots.is_empty() || max_period == 0 {
+            return cycles;
+        }
+        let center = self.roots.iter().sum::<Complex<f32>>() / self.roots.len() as f32;
+        let extent = 1.5
+            * self
+                .roots
+                .iter()
+                .map(|root| (root - center).norm())
+                .fold(1.0, f32::max);
+        let mut d = Vec::with_capacity(self.double.derivatives.len() + 1);
+        let mut points = 0;
+        for i in 0..CYCLE_SEEDS {
+            for j in 0..CYCLE_SEEDS {
+                let offset = Complex::new(i as f32 + 0.5, j as f32 + 0.5) / CYCLE_SEEDS as f32;
+                let seed = center + extent * (2.0 * offset - Complex::new(1.0, 1.0));
+                let z = self.orbit(Complex::new(seed.re as f64, seed.im as f64)).z;
+                if !z.is_finite()
+                    || self.basin(z).is_some()
+                    || cycles
+                        .iter()
+                        .any(|cycle| cycle.contains(z, 10.0 * CYCLE_TOLERANCE as f32))
+                {
+                    continue;
+                }
+                let z = Complex::new(z.re as f64, z.im as f64);
+                let Some(cycle) = self.periodic_orbit(z, max_period, &mut d) else {
+                    continue;
+                };
+                if points + cycle.period() > MAX_CYCLE_POINTS {
+                    return cycles;
+                }
+                points += cycle.period();
+                cycles.push(cycle);
+            }
+        }
+        cycles
+    }
+
+    // fixed points take the color of the root closest to them, like the nova fragment shader
+    fn nova_color(&self, orbit: &Orbit) -> [u8; 4] {
+        let [r, g, b] = match (orbit.state, self.closest_root(orbit.z)) {
+            (OrbitState::Converged, Some(root)) => {
+                self.shade(self.colors[root], orbit.iterations as f32)
+            }
+            (OrbitState::Escaped, _) => [orbit.iterations as f32 / self.max_iterations as f32; 3],
+            _ => self.no_basin_color,
+        };
+        [to_byte(r), to_byte(g), to_byte(b), 255]
+    }
+
+    fn pixel_orbit<T: Real>(
+        &self,
+        iteration: &Iteration<T>,
+        pixel: Complex<T>,
+        precision: usize,
+    ) -> Orbit {
+        match self.nova {
+            Nova::Off => self.orbit_in(iteration, pixel),
+            Nova::Julia | Nova::Mandelbrot => self.nova_orbit_in(iteration, pixel, precision),
+        }
+    }
+
+    // the orbit is followed in arbitrary precision if enabled, either way it's colored the same
+    pub fn pixel_color(&self, x: u32, y: u32, width: u32, height: u32) -> [u8; 4] {
+        let orbit = match &self.precise {
+            Some(precise) => {
+                let precision = self.precision(width, height);
+                let center = Complex::new(
+                    self.precise_center.re.clone().with_precision(precision),
+                    self.precise_center.im.clone().with_precision(precision),
+                );
+                let pixel = self.pixel(center, x, y, width, height);
+                self.pixel_orbit(precise, pixel, precision)
+            }
+            None => self.pixel_orbit(&self.double, self.pixel_to_complex(x, y, width, height), 0),
+        };
+        if self.nova != Nova::Off {
+            return self.nova_color(&orbit);
+        }
+        let [r, g, b] = match self.basin(orbit.z) {
+            Some(index) => {
+                let iterations = if self.smooth {
+                    self.smooth_iterations(&orbit, self.roots[index])
+                } else {
+                    orbit.iterations as f32
+                };
+                self.shade(self.colors[index], iterations)
+            }
+            None => self
+                .cycle_basin(orbit.z)
+                .map_or(self.no_basin_color, cycle_color),
+        };
+        [to_byte(r), to_byte(g), to_byte(b), 255]
+    }
+
+    fn render_tile(&self, tile: Tile, width: u16, height: u16) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(tile.width as usize * tile.height as usize * 4);
+        for y in tile.y..tile.y + tile.height {
+            for x in tile.x..tile.x + tile.width {
+                bytes.extend_from_slice(&self.pixel_color(
+                    x as u32,
+                    y as u32,
+                    width as u32,
+                    height as u32,
+                ));
+            }
+        }
+        bytes
+    }
+
+    pub fn render(&self, width: u16, height: u16) -> Image {
+        self.render_tiled(
+            width,
+            height,
+            TileOptions::default(),
+            |_, _| {},
+            &CancelToken::new(),
+        )
+        .expect("render without cancellation always completes")
+    }
+
+    // renders the image tile by tile on `options.threads` threads, `progress` receives the
+    // number of completed tiles and the total, returns None if `cancel` skipped any tile
+    pub fn render_tiled<F>(
+        &self,
+        width: u16,
+        height: u16,
+        options: TileOptions,
+        progress: F,
+        cancel: &CancelToken,
+    ) -> Option<Image>
+    where
+        F: Fn(usize, usize) + Sync,
+    {
+        let tiles = build_tiles(width, height, options.tile_size);
+        let next_tile = AtomicUsize::new(0);
+        let completed = AtomicUsize::new(0);
+        let bytes = Mutex::new(vec![0; width as usize * height as usize * 4]);
+
+        let worker = || loop {
+            if cancel.is_cancelled() {
+                break;
+            }
+            let index = next_tile.fetch_add(1, Ordering::Relaxed);
+            let Some(&tile) = tiles.get(index) else {
+                break;
+            };
+            let tile_bytes = self.render_tile(tile, width, height);
+            {
+                let mut bytes = bytes.lock().unwrap();
+                let row_len = tile.width as usize * 4;
+                for (row, tile_row) in tile_bytes.chunks_exact(row_len).enumerate() {
+                    let start = ((tile.y as usize + row) * width as usize + tile.x as usize) * 4;
+                    bytes[start..start + row_len].copy_from_slice(tile_row);
+                }
+            }
+            progress(completed.fetch_add(1, Ordering::Relaxed) + 1, tiles.len());
+        };
+
+        let threads = options.threads.clamp(1, tiles.len().max(1));
+        if threads == 1 {
+            worker();
+        } else {
+            thread::scope(|scope| {
+                for _ in 0..threads {
+                    scope.spawn(worker);
+                }
+            });
+        }
+
+        if completed.into_inner() < tiles.len() {
+            return None;
+        }
+        Some(Image {
+            bytes: bytes.into_inner().unwrap(),
+            width,
+            height,
+        })
+    }
+}
+
+fn to_byte(channel: f32) -> u8 {
+    (channel.clamp(0.0, 1.0) * 255.0).round() as u8
+}
@@ -0,0 +1,393 @@
+use macroquad::math::{dvec2, vec2, DVec2, Vec2};
+use num_complex::Complex;
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, io, path::Path};
+
+use crate::{
+    error::check_roots, AspectPolicy, BigFloat, CpuRenderer, FractalError, Method, Nova, Real,
+    Shading, Viewport, DEFAULT_CONVERGENCE_RADIUS, DEFAULT_TOLERANCE, MAX_CYCLE_PERIOD,
+};
+
+// version 2 replaced the f32 ranges of the view by its center and scale, version 3 stores the
+// center in decimal instead of f64
+pub const SCENE_VERSION: u32 = 3;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Scene {
+    pub version: u32,
+    pub roots: Vec<[f32; 2]>,
+    pub colors: Vec<[f32; 3]>,
+    pub max_iterations: u32,
+    // the view as in a Viewport, the center in as many digits as deep zooms need
+    #[serde(default = "default_center")]
+    pub center: [BigFloat; 2],
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    #[serde(default)]
+    pub aspect: AspectPolicy,
+    #[serde(default)]
+    pub method: Method,
+    #[serde(default = "default_relaxation")]
+    pub relaxation: [f32; 2],
+    #[serde(default)]
+    pub nova: Nova,
+    #[serde(default)]
+    pub nova_constant: [f32; 2],
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f32,
+    #[serde(default)]
+    pub shading: Shading,
+    #[serde(default = "default_gamma")]
+    pub gamma: f32,
+    #[serde(default)]
+    pub smooth: bool,
+    #[serde(default = "default_convergence_radius")]
+    pub convergence_radius: f32,
+    #[serde(default)]
+    pub no_basin_color: [f32; 3],
+    // 0 leaves cycle detection off
+    #[serde(default)]
+    pub max_cycle_period: u32,
+    // radians the view is turned by around its center
+    #[serde(default)]
+    pub rotation: f32,
+    // the CPU renders the view in arbitrary precision, which lets it zoom beyond f64
+    #[serde(default)]
+    pub arbitrary_precision: bool,
+}
+
+// what older versions stored in place of the center
+#[derive(Deserialize)]
+struct LegacyView {
+    version: u32,
+    // version 1, the view as ranges
+    real_range: Option<[f32; 2]>,
+    imag_range: Option<[f32; 2]>,
+    // version 2, the center in f64
+    center: Option<[f64; 2]>,
+}
+
+fn default_center() -> [BigFloat; 2] {
+    [BigFloat::from_f64(0.0), BigFloat::from_f64(0.0)]
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+fn default_convergence_radius() -> f32 {
+    DEFAULT_CONVERGENCE_RADIUS
+}
+
+fn default_tolerance() -> f32 {
+    DEFAULT_TOLERANCE
+}
+
+fn default_gamma() -> f32 {
+    1.0
+}
+
+fn default_relaxation() -> [f32; 2] {
+    [1.0, 0.0]
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    UnsupportedVersion(u32),
+    Fractal(FractalError),
+    InvalidRoot(usize),
+    InvalidColor(usize),
+    InvalidRange(&'static str),
+    InvalidParameter(&'static str),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io(err) => write!(f, "{}", err),
+            SceneError::Parse(err) => write!(f, "malformed scene: {}", err),
+            SceneError::UnsupportedVersion(version) => write!(
+                f,
+                "scene version {} is not supported (latest is {})",
+                version, SCENE_VERSION
+            ),
+            SceneError::Fractal(err) => write!(f, "{}", err),
+            SceneError::InvalidRoot(index) => write!(f, "root {} is not a finite number", index),
+            SceneError::InvalidColor(index) => {
+                write!(f, "color {} has components outside [0, 1]", index)
+            }
+            SceneError::InvalidRange(range) => {
+                write!(f, "{} range must be two distinct finite numbers", range)
+            }
+            SceneError::InvalidParameter(name) => write!(f, "malformed {} parameter", name),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<io::Error> for SceneError {
+    fn from(err: io::Error) -> Self {
+        SceneError::Io(err)
+    }
+}
+
+impl From<FractalError> for SceneError {
+    fn from(err: FractalError) -> Self {
+        SceneError::Fractal(err)
+    }
+}
+
+impl From<serde_json::Error> for SceneError {
+    fn from(err: serde_json::Error) -> Self {
+        SceneError::Parse(err)
+    }
+}
+
+fn valid_range(range: [f32; 2]) -> bool {
+    range.iter().all(|bound| bound.is_finite()) && range[0] != range[1]
+}
+
+impl Scene {
+    pub fn new(
+        roots: &[Vec2],
+        colors: &[[f32; 3]],
+        max_iterations: u32,
+        center: DVec2,
+        scale: f64,
+    ) -> Self {
+        Self {
+            version: SCENE_VERSION,
+            roots: roots.iter().map(|root| root.to_array()).collect(),
+            colors: colors.to_vec(),
+            max_iterations,
+            center: [BigFloat::from_f64(center.x), BigFloat::from_f64(center.y)],
+            scale,
+            aspect: AspectPolicy::default(),
+            method: Method::default(),
+            relaxation: default_relaxation(),
+            nova: Nova::default(),
+            nova_constant: [0.0, 0.0],
+            tolerance: DEFAULT_TOLERANCE,
+            shading: Shading::default(),
+            gamma: default_gamma(),
+            smooth: false,
+            convergence_radius: DEFAULT_CONVERGENCE_RADIUS,
+            no_basin_color: [0.0, 0.0, 0.0],
+            max_cycle_period: 0,
+            rotation: 0.0,
+            arbitrary_precision: false,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), SceneError> {
+        if self.version == 0 || self.version > SCENE_VERSION {
+            return Err(SceneError::UnsupportedVersion(self.version));
+        }
+        check_roots(self.roots.len(), self.colors.len())?;
+        if let Some(index) = self
+            .roots
+            .iter()
+            .position(|root| !root.iter().all(|value| value.is_finite()))
+        {
+            return Err(SceneError::InvalidRoot(index));
+        }
+        if let Some(index) = self
+            .colors
+            .iter()
+            .position(|color| !color.iter().all(|value| (0.0..=1.0).contains(value)))
+        {
+            return Err(SceneError::InvalidColor(index));
+        }
+        if !self.method.is_valid() {
+            return Err(SceneError::InvalidParameter("method"));
+        }
+        if !self.relaxation.iter().all(|value| value.is_finite()) {
+            return Err(SceneError::InvalidParameter("relaxation"));
+        }
+        if !self.nova_constant.iter().all(|value| value.is_finite()) {
+            return Err(SceneError::InvalidParameter("nova constant"));
+        }
+        if !(self.tolerance.is_finite() && self.tolerance >= 0.0) {
+            return Err(SceneError::InvalidParameter("tolerance"));
+        }
+        if !(self.gamma.is_finite() && self.gamma > 0.0) {
+            return Err(SceneError::InvalidParameter("gamma"));
+        }
+        if !(self.convergence_radius.is_finite() && self.convergence_radius > 0.0) {
+            return Err(SceneError::InvalidParameter("convergence radius"));
+        }
+        if !self
+            .no_basin_color
+            .iter()
+            .all(|value| (0.0..=1.0).contains(value))
+        {
+            return Err(SceneError::InvalidParameter("no basin color"));
+        }
+        if self.max_cycle_period > MAX_CYCLE_PERIOD {
+            return Err(SceneError::InvalidParameter("cycle period"));
+        }
+        if !self.rotation.is_finite() {
+            return Err(SceneError::InvalidParameter("rotation"));
+        }
+        if self.center.iter().any(BigFloat::is_nan) {
+            return Err(SceneError::InvalidParameter("center"));
+        }
+        if !(self.scale.is_finite() && self.scale > 0.0) {
+            return Err(SceneError::InvalidParameter("scale"));
+        }
+        Ok(())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, SceneError> {
+        let mut value: serde_json::Value = serde_json::from_str(json)?;
+        let version = value.get("version").and_then(serde_json::Value::as_u64);
+        let legacy = match version {
+            Some(version) if version > 0 && version < SCENE_VERSION as u64 => {
+                let legacy = LegacyView::deserialize(&value)?;
+                if let Some(fields) = value.as_object_mut() {
+                    fields.remove("center");
+                }
+                Some(legacy)
+            }
+            _ => None,
+        };
+        let mut scene = Scene::deserialize(&value)?;
+        if let Some(legacy) = legacy {
+            scene.migrate(legacy)?;
+        }
+        scene.validate()?;
+        Ok(scene)
+    }
+
+    // brings the view of an older scene to the current version
+    fn migrate(&mut self, legacy: LegacyView) -> Result<(), SceneError> {
+        if legacy.version == 1 {
+            self.set_ranges(
+                legacy.real_range.ok_or(SceneError::InvalidRange("real"))?,
+                legacy
+                    .imag_range
+                    .ok_or(SceneError::InvalidRange("imaginary"))?,
+            )?;
+        } else if let Some(center) = legacy.center {
+            self.set_center(DVec2::from_array(center));
+        }
+        self.version = SCENE_VERSION;
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("a scene is always serializable")
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SceneError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SceneError> {
+        fs::write(path, self.to_json())?;
+        Ok(())
+    }
+
+    pub fn get_roots(&self) -> Vec<Vec2> {
+        self.roots.iter().map(|&root| root.into()).collect()
+    }
+
+    // the closest f64 to the center
+    pub fn get_center(&self) -> DVec2 {
+        dvec2(self.center[0].to_f64(), self.center[1].to_f64())
+    }
+
+    pub fn set_center(&mut self, center: DVec2) {
+        self.center = [BigFloat::from_f64(center.x), BigFloat::from_f64(center.y)];
+    }
+
+    pub fn get_precise_center(&self) -> Complex<BigFloat> {
+        Complex::new(self.center[0].clone(), self.center[1].clone())
+    }
+
+    // the view fitting both ranges on any screen
+    pub fn set_ranges(
+        &mut self,
+        real_range: [f32; 2],
+        imag_range: [f32; 2],
+    ) -> Result<(), SceneError> {
+        if !valid_range(real_range) {
+            return Err(SceneError::InvalidRange("real"));
+        }
+        if !valid_range(imag_range) {
+            return Err(SceneError::InvalidRange("imaginary"));
+        }
+        let (real_range, imag_range) = (
+            Vec2::from(real_range).as_dvec2(),
+            Vec2::from(imag_range).as_dvec2(),
+        );
+        self.set_center(dvec2(
+            (real_range.x + real_range.y) / 2.0,
+            (imag_range.x + imag_range.y) / 2.0,
+        ));
+        self.scale = dvec2(real_range.y - real_range.x, imag_range.y - imag_range.x)
+            .abs()
+            .max_element()
+            / 2.0;
+        self.aspect = AspectPolicy::Fit;
+        Ok(())
+    }
+
+    // the ranges of the view on a square screen
+    pub fn get_real_range(&self) -> Vec2 {
+        let center = self.get_center();
+        dvec2(center.x - self.scale, center.x + self.scale).as_vec2()
+    }
+
+    pub fn get_imag_range(&self) -> Vec2 {
+        let center = self.get_center();
+        dvec2(center.y - self.scale, center.y + self.scale).as_vec2()
+    }
+
+    // the view on a screen of `size` pixels
+    pub fn viewport(&self, size: Vec2) -> Viewport {
+        let mut viewport = Viewport::new(self.get_center(), self.scale, size);
+        viewport.set_precise_center(self.get_precise_center());
+        viewport.set_aspect(self.aspect);
+        viewport.set_rotation(self.rotation);
+        viewport.set_arbitrary_precision(self.arbitrary_precision);
+        viewport
+    }
+
+    pub fn get_relaxation(&self) -> Vec2 {
+        vec2(self.relaxation[0], self.relaxation[1])
+    }
+
+    pub fn get_nova_constant(&self) -> Vec2 {
+        vec2(self.nova_constant[0], self.nova_constant[1])
+    }
+
+    // renders the view on an image of `width` x `height` pixels
+    pub fn cpu_renderer(&self, width: u16, height: u16) -> Result<CpuRenderer, SceneError> {
+        self.validate()?;
+        let mut renderer = CpuRenderer::new(
+            self.get_roots(),
+            self.colors.clone(),
+            self.max_iterations,
+            self.get_real_range(),
+            self.get_imag_range(),
+        )?;
+        renderer.set_method(self.method);
+        renderer.set_relaxation(Complex::new(self.relaxation[0], self.relaxation[1]));
+        renderer.set_nova(
+            self.nova,
+            Complex::new(self.nova_constant[0], self.nova_constant[1]),
+        );
+        renderer.set_tolerance(self.tolerance);
+        renderer.set_shading(self.shading, self.gamma);
+        renderer.set_smooth(self.smooth);
+        renderer.set_no_basin(self.convergence_radius, self.no_basin_color);
+        renderer.set_max_cycle_period(self.max_cycle_period);
+        renderer.set_viewport(&self.viewport(vec2(width as f32, height as f32)));
+        renderer.set_arbitrary_precision(self.arbitrary_precision);
+        Ok(renderer)
+    }
+}
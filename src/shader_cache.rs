@@ -0,0 +1,147 @@
+use crate::{fractal_shader::ShaderKey, FractalError, FractalShader};
+
+// macroquad can't hold more than 32 pipelines, including its own
+pub const DEFAULT_SHADER_CACHE_CAPACITY: usize = 8;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ShaderCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    // lookups of keys that already failed to compile
+    pub failures: u64,
+    pub evictions: u64,
+    pub entries: usize,
+    pub capacity: usize,
+}
+
+// what the cache compiles and deletes, the GPU pipeline of a FractalShader
+pub trait CompiledShader: Sized {
+    fn compile(key: ShaderKey) -> Result<Self, FractalError>;
+
+    fn delete(&mut self);
+}
+
+impl CompiledShader for FractalShader {
+    fn compile(key: ShaderKey) -> Result<Self, FractalError> {
+        FractalShader::new(key)
+    }
+
+    fn delete(&mut self) {
+        FractalShader::delete(self)
+    }
+}
+
+// compiled shaders by code generation options, least recently used first, the cache owns
+// them and only lends them out so none is deleted while still in use
+pub struct ShaderCache<S: CompiledShader = FractalShader> {
+    entries: Vec<(ShaderKey, S)>,
+    // keys that failed to compile, kept so they aren't recompiled on every frame
+    failures: Vec<(ShaderKey, FractalError)>,
+    // the shader last handed out, which is being drawn with and never evicted
+    active: Option<ShaderKey>,
+    capacity: usize,
+    stats: ShaderCacheStats,
+}
+
+impl<S: CompiledShader> Default for ShaderCache<S> {
+    fn default() -> Self {
+        Self::new(DEFAULT_SHADER_CACHE_CAPACITY)
+    }
+}
+
+impl<S: CompiledShader> ShaderCache<S> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            failures: Vec::new(),
+            active: None,
+            capacity: capacity.max(1),
+            stats: ShaderCacheStats::default(),
+        }
+    }
+
+    // the shader for `key` becomes the active one, a failed compile leaves the active shader
+    // as it was
+    pub fn get(&mut self, key: ShaderKey) -> Result<&mut S, FractalError> {
+        if let Some(index) = self.entries.iter().position(|(entry, _)| *entry == key) {
+            self.stats.hits += 1;
+            let entry = self.entries.remove(index);
+            self.entries.push(entry);
+        } else {
+            if let Some((_, err)) = self.failures.iter().find(|(entry, _)| *entry == key) {
+                self.stats.failures += 1;
+                return Err(err.clone());
+            }
+
+            self.stats.misses += 1;
+            let shader = match S::compile(key) {
+                Ok(shader) => shader,
+                Err(err) => {
+                    self.failures.push((key, err.clone()));
+                    return Err(err);
+                }
+            };
+            self.entries.push((key, shader));
+        }
+        self.active = Some(key);
+        self.evict(self.capacity);
+        Ok(&mut self
+            .entries
+            .last_mut()
+            .expect("the entry was just pushed")
+            .1)
+    }
+
+    pub fn get_active(&self) -> Option<&S> {
+        let active = self.active?;
+        self.entries
+            .iter()
+            .find(|(entry, _)| *entry == active)
+            .map(|(_, shader)| shader)
+    }
+
+    pub fn get_active_mut(&mut self) -> Option<&mut S> {
+        let active = self.active?;
+        self.entries
+            .iter_mut()
+            .find(|(entry, _)| *entry == active)
+            .map(|(_, shader)| shader)
+    }
+
+    pub fn get_active_key(&self) -> Option<ShaderKey> {
+        self.active
+    }
+
+    // removes the least recently used shaders except the active one
+    fn evict(&mut self, len: usize) {
+        while self.entries.len() > len {
+            let Some(index) = self
+                .entries
+                .iter()
+                .position(|(entry, _)| Some(*entry) != self.active)
+            else {
+                break;
+            };
+            let (_, mut shader) = self.entries.remove(index);
+            shader.delete();
+            self.stats.evictions += 1;
+        }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        self.evict(self.capacity);
+    }
+
+    pub fn contains(&self, key: ShaderKey) -> bool {
+        self.entries.iter().any(|(entry, _)| *entry == key)
+    }
+
+    pub fn stats(&self) -> ShaderCacheStats {
+        ShaderCacheStats {
+            entries: self.entries.len(),
+            capacity: self.capacity,
+            ..self.stats
+        }
+    }
+}
@@ -5,17 +5,24 @@ precision highp float;
 in vec3 position;
 in vec2 texcoord;
 out vec2 complex;
+// the point relative to the center, deep zoom adds it to the center in double-float
+out vec2 offset;
 
 uniform mat4 Model;
 uniform mat4 Projection;
 
-uniform vec2 realRange;
-uniform vec2 imagRange;
+uniform vec2 center;
+// half the span of the view along each screen axis
+uniform vec2 extent;
+// cos and sin of the view rotation
+uniform vec2 rotation;
 
 void main() {
-	complex = vec2(
-		texcoord.x * (realRange.y - realRange.x) + realRange.x,
-		texcoord.y * (imagRange.y - imagRange.x) + imagRange.x
+	vec2 local = (texcoord * 2.0 - 1.0) * extent;
+	offset = vec2(
+		rotation.x * local.x - rotation.y * local.y,
+		rotation.y * local.x + rotation.x * local.y
 	);
+	complex = center + offset;
 	gl_Position = Projection * Model * vec4(position, 1);
 }
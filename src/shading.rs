@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+// how the color of a basin is scaled by the iterations needed to converge
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Shading {
+    #[default]
+    Flat,
+    Linear,
+    Log,
+    Gamma,
+}
+
+impl Shading {
+    pub const ALL: [Shading; 4] = [Shading::Flat, Shading::Linear, Shading::Log, Shading::Gamma];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shading::Flat => "flat",
+            Shading::Linear => "linear",
+            Shading::Log => "log",
+            Shading::Gamma => "gamma",
+        }
+    }
+
+    // factor applied to the basin color, 1 when converging immediately down to 0 at the limit,
+    // `iterations` is fractional with smooth coloring
+    pub fn brightness(&self, iterations: f32, max_iterations: u32, gamma: f32) -> f32 {
+        let max_iterations = max_iterations.max(1) as f32;
+        let t = iterations / max_iterations;
+        match self {
+            Shading::Flat => 1.0,
+            Shading::Linear => 1.0 - t,
+            Shading::Log => 1.0 - (1.0 + iterations).ln() / (1.0 + max_iterations).ln(),
+            Shading::Gamma => (1.0 - t).powf(gamma),
+        }
+    }
+
+    // GLSL version of `brightness`, with `iterations`, `t` and the `gamma` uniform in scope
+    pub(crate) fn glsl_brightness(&self) -> &'static str {
+        match self {
+            Shading::Flat => "1.0",
+            Shading::Linear => "1.0 - t",
+            Shading::Log => "1.0 - log(1.0 + iterations) / log(1.0 + float(max(maxIterations, 1)))",
+            Shading::Gamma => "pow(1.0 - t, gamma)",
+        }
+    }
+}
+
+impl fmt::Display for Shading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Shading {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Shading::ALL
+            .into_iter()
+            .find(|shading| shading.name() == name)
+            .ok_or_else(|| format!("unknown shading '{}'", name))
+    }
+}
@@ -0,0 +1,237 @@
+#[cfg(target_arch = "wasm32")]
+use sapp_jsutils::{JsObject, JsObjectWeak};
+
+use crate::{
+    export::{
+        format_big_complex, format_groups, format_vec2, parse_big_complex, parse_groups, parse_vec2,
+    },
+    Scene, SceneError,
+};
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    // defined in url_state.js
+    fn url_state_replace_query(query: JsObjectWeak);
+}
+
+// query string keys, kept short since the whole state ends up in a shared link
+const ROOTS: &str = "roots";
+const COLORS: &str = "colors";
+const ITERATIONS: &str = "iter";
+const CENTER: &str = "center";
+const SCALE: &str = "scale";
+const ASPECT: &str = "aspect";
+// only read, links from before the center and scale carry the view as ranges
+const REAL_RANGE: &str = "real";
+const IMAG_RANGE: &str = "imag";
+const METHOD: &str = "method";
+const RELAXATION: &str = "relax";
+const NOVA: &str = "nova";
+const NOVA_CONSTANT: &str = "c";
+const TOLERANCE: &str = "tol";
+const SHADING: &str = "shade";
+const GAMMA: &str = "gamma";
+const SMOOTH: &str = "smooth";
+const CONVERGENCE_RADIUS: &str = "radius";
+const NO_BASIN_COLOR: &str = "nobasin";
+const MAX_CYCLE_PERIOD: &str = "cycles";
+const ROTATION: &str = "rot";
+const ARBITRARY_PRECISION: &str = "precise";
+
+pub fn scene_to_url_params(scene: &Scene) -> Vec<(&'static str, String)> {
+    vec![
+        (
+            ROOTS,
+            format_groups(scene.roots.iter().map(|root| &root[..])),
+        ),
+        (
+            COLORS,
+            format_groups(scene.colors.iter().map(|color| &color[..])),
+        ),
+        (ITERATIONS, scene.max_iterations.to_string()),
+        (CENTER, format_big_complex(&scene.get_precise_center())),
+        (SCALE, scene.scale.to_string()),
+        (ASPECT, scene.aspect.to_string()),
+        (METHOD, scene.method.to_string()),
+        (RELAXATION, format_vec2(scene.get_relaxation())),
+        (NOVA, scene.nova.to_string()),
+        (NOVA_CONSTANT, format_vec2(scene.get_nova_constant())),
+        (TOLERANCE, scene.tolerance.to_string()),
+        (SHADING, scene.shading.to_string()),
+        (GAMMA, scene.gamma.to_string()),
+        (SMOOTH, scene.smooth.to_string()),
+        (CONVERGENCE_RADIUS, scene.convergence_radius.to_string()),
+        (
+            NO_BASIN_COLOR,
+            format_groups([&scene.no_basin_color[..]].into_iter()),
+        ),
+        (MAX_CYCLE_PERIOD, scene.max_cycle_period.to_string()),
+        (ROTATION, scene.rotation.to_string()),
+        (ARBITRARY_PRECISION, scene.arbitrary_precision.to_string()),
+    ]
+}
+
+// percent encoded like a form, e.g. "roots=1+0%3B-1+0&iter=30"
+pub fn url_query(params: &[(&str, String)]) -> String {
+    let encode = |text: &str| {
+        text.bytes()
+            .map(|byte| match byte {
+                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'*' => {
+                    (byte as char).to_string()
+                }
+                b' ' => "+".to_owned(),
+                _ => format!("%{:02X}", byte),
+            })
+            .collect::<String>()
+    };
+    params
+        .iter()
+        .map(|(key, value)| format!("{}={}", encode(key), encode(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+// swaps the whole query string at once without adding a history entry, quad_url pushes one
+// per parameter, has no effect outside WASM
+pub fn replace_url_params(params: &[(&str, String)]) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let query = JsObject::string(&url_query(params));
+        unsafe {
+            url_state_replace_query(query.weak());
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = params;
+}
+
+// parameters missing from the url keep the value they have in `base`
+pub fn scene_from_url_params<'a, I>(params: I, base: &Scene) -> Result<Scene, SceneError>
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    let mut scene = base.clone();
+    let (mut real_range, mut imag_range) = (None, None);
+    for (key, value) in params {
+        match key {
+            ROOTS => {
+                scene.roots = parse_groups(value, 2)
+                    .ok_or(SceneError::InvalidParameter(ROOTS))?
+                    .iter()
+                    .map(|root| [root[0], root[1]])
+                    .collect()
+            }
+            COLORS => {
+                scene.colors = parse_groups(value, 3)
+                    .ok_or(SceneError::InvalidParameter(COLORS))?
+                    .iter()
+                    .map(|color| [color[0], color[1], color[2]])
+                    .collect()
+            }
+            ITERATIONS => {
+                scene.max_iterations = value
+                    .parse()
+                    .map_err(|_| SceneError::InvalidParameter(ITERATIONS))?
+            }
+            CENTER => {
+                let center =
+                    parse_big_complex(value).ok_or(SceneError::InvalidParameter(CENTER))?;
+                scene.center = [center.re, center.im]
+            }
+            SCALE => {
+                scene.scale = value
+                    .parse()
+                    .map_err(|_| SceneError::InvalidParameter(SCALE))?
+            }
+            ASPECT => {
+                scene.aspect = value
+                    .parse()
+                    .map_err(|_| SceneError::InvalidParameter(ASPECT))?
+            }
+            REAL_RANGE => {
+                real_range =
+                    Some(parse_vec2(value).ok_or(SceneError::InvalidParameter(REAL_RANGE))?)
+            }
+            IMAG_RANGE => {
+                imag_range =
+                    Some(parse_vec2(value).ok_or(SceneError::InvalidParameter(IMAG_RANGE))?)
+            }
+            METHOD => {
+                scene.method = value
+                    .parse()
+                    .map_err(|_| SceneError::InvalidParameter(METHOD))?
+            }
+            RELAXATION => {
+                scene.relaxation = parse_vec2(value)
+                    .ok_or(SceneError::InvalidParameter(RELAXATION))?
+                    .to_array()
+            }
+            NOVA => {
+                scene.nova = value
+                    .parse()
+                    .map_err(|_| SceneError::InvalidParameter(NOVA))?
+            }
+            NOVA_CONSTANT => {
+                scene.nova_constant = parse_vec2(value)
+                    .ok_or(SceneError::InvalidParameter(NOVA_CONSTANT))?
+                    .to_array()
+            }
+            TOLERANCE => {
+                scene.tolerance = value
+                    .parse()
+                    .map_err(|_| SceneError::InvalidParameter(TOLERANCE))?
+            }
+            SHADING => {
+                scene.shading = value
+                    .parse()
+                    .map_err(|_| SceneError::InvalidParameter(SHADING))?
+            }
+            GAMMA => {
+                scene.gamma = value
+                    .parse()
+                    .map_err(|_| SceneError::InvalidParameter(GAMMA))?
+            }
+            SMOOTH => {
+                scene.smooth = value
+                    .parse()
+                    .map_err(|_| SceneError::InvalidParameter(SMOOTH))?
+            }
+            CONVERGENCE_RADIUS => {
+                scene.convergence_radius = value
+                    .parse()
+                    .map_err(|_| SceneError::InvalidParameter(CONVERGENCE_RADIUS))?
+            }
+            NO_BASIN_COLOR => {
+                scene.no_basin_color = match parse_groups(value, 3).as_deref() {
+                    Some([color]) => [color[0], color[1], color[2]],
+                    _ => return Err(SceneError::InvalidParameter(NO_BASIN_COLOR)),
+                }
+            }
+            MAX_CYCLE_PERIOD => {
+                scene.max_cycle_period = value
+                    .parse()
+                    .map_err(|_| SceneError::InvalidParameter(MAX_CYCLE_PERIOD))?
+            }
+            ROTATION => {
+                scene.rotation = value
+                    .parse()
+                    .map_err(|_| SceneError::InvalidParameter(ROTATION))?
+            }
+            ARBITRARY_PRECISION => {
+                scene.arbitrary_precision = value
+                    .parse()
+                    .map_err(|_| SceneError::InvalidParameter(ARBITRARY_PRECISION))?
+            }
+            _ => {}
+        }
+    }
+    // a single range keeps the other one of the current view
+    if real_range.is_some() || imag_range.is_some() {
+        scene.set_ranges(
+            real_range.unwrap_or(scene.get_real_range()).to_array(),
+            imag_range.unwrap_or(scene.get_imag_range()).to_array(),
+        )?;
+    }
+    scene.validate()?;
+    Ok(scene)
+}
@@ -0,0 +1,332 @@
+use macroquad::math::{dvec2, vec2, DVec2, Vec2};
+use num_complex::Complex;
+use serde::{Deserialize, Serialize};
+use std::{f32::consts::PI, fmt, str::FromStr};
+
+use crate::{BigFloat, Real};
+
+// below this scale relative to the center f32 has only thousands of steps left across the
+// screen, the shader switches to double-float coordinates
+const DEEP_ZOOM_SCALE: f64 = 5.0e-4;
+// smallest scale relative to the center, pixels stay well above the double-float error
+pub const MIN_SCALE: f64 = 1.0e-9;
+// the same with arbitrary precision, pixels stay well above the smallest f64
+const MIN_PRECISE_SCALE: f64 = 1.0e-280;
+// bits of the center beyond the pixel size, so zooming further doesn't lose its place
+const CENTER_GUARD_BITS: usize = 64;
+
+// the side of the screen spanning twice the scale, pixels stay square with every policy
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AspectPolicy {
+    // the shorter side, so a view of the same scale is always visible whole
+    #[default]
+    Fit,
+    // the longer side, so the screen is always covered by it
+    Fill,
+    Width,
+    Height,
+}
+
+impl AspectPolicy {
+    pub const ALL: [AspectPolicy; 4] = [
+        AspectPolicy::Fit,
+        AspectPolicy::Fill,
+        AspectPolicy::Width,
+        AspectPolicy::Height,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AspectPolicy::Fit => "fit",
+            AspectPolicy::Fill => "fill",
+            AspectPolicy::Width => "width",
+            AspectPolicy::Height => "height",
+        }
+    }
+
+    fn reference(&self, size: Vec2) -> f32 {
+        match self {
+            AspectPolicy::Fit => size.min_element(),
+            AspectPolicy::Fill => size.max_element(),
+            AspectPolicy::Width => size.x,
+            AspectPolicy::Height => size.y,
+        }
+    }
+}
+
+impl fmt::Display for AspectPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for AspectPolicy {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        AspectPolicy::ALL
+            .into_iter()
+            .find(|aspect| aspect.name() == name)
+            .ok_or_else(|| format!("unknown aspect policy '{}'", name))
+    }
+}
+
+// the part of the complex plane shown on a screen of `size` pixels, screen y grows
+// downwards along with the imaginary part like the texture coordinates of the fractal
+#[derive(Clone, Debug, PartialEq)]
+pub struct Viewport {
+    // the closest f64 to the precise center, the shader gets it split in two f32
+    center: DVec2,
+    // only ever moved by offsets, so zooms beyond f64 keep their place
+    precise_center: Complex<BigFloat>,
+    // the CPU renders what the shader can't, zooming goes beyond MIN_SCALE then
+    arbitrary_precision: bool,
+    // half the span of the side picked by the aspect policy
+    scale: f64,
+    // radians the plane is turned by around the center
+    rotation: f32,
+    aspect: AspectPolicy,
+    size: Vec2,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self::new(dvec2(0.0, 0.0), 1.0, vec2(1.0, 1.0))
+    }
+}
+
+impl Viewport {
+    pub fn new(center: DVec2, scale: f64, size: Vec2) -> Self {
+        Self {
+            center,
+            precise_center: Complex::new(
+                BigFloat::from_f64(center.x),
+                BigFloat::from_f64(center.y),
+            ),
+            arbitrary_precision: false,
+            scale,
+            rotation: 0.0,
+            aspect: AspectPolicy::default(),
+            size,
+        }
+    }
+
+    // the smallest unrotated view of `size` pixels containing both ranges
+    pub fn from_ranges(real_range: Vec2, imag_range: Vec2, size: Vec2) -> Self {
+        let mut viewport = Self::new(dvec2(0.0, 0.0), 1.0, size);
+        viewport.set_ranges(real_range, imag_range);
+        viewport
+    }
+
+    // centers the view on the ranges and scales it so they're visible whole
+    pub fn set_ranges(&mut self, real_range: Vec2, imag_range: Vec2) {
+        let (real_range, imag_range) = (real_range.as_dvec2(), imag_range.as_dvec2());
+        self.set_center(dvec2(
+            (real_range.x + real_range.y) / 2.0,
+            (imag_range.x + imag_range.y) / 2.0,
+        ));
+        let extent = dvec2(real_range.y - real_range.x, imag_range.y - imag_range.x).abs() / 2.0;
+        let reference = self.aspect.reference(self.size()) as f64;
+        self.scale = (extent / self.size().as_dvec2() * reference).max_element();
+    }
+
+    pub fn get_center(&self) -> DVec2 {
+        self.center
+    }
+
+    // non-finite centers are ignored
+    pub fn set_center(&mut self, center: DVec2) {
+        self.set_precise_center(Complex::new(
+            BigFloat::from_f64(center.x),
+            BigFloat::from_f64(center.y),
+        ));
+    }
+
+    pub fn get_precise_center(&self) -> &Complex<BigFloat> {
+        &self.precise_center
+    }
+
+    pub fn set_precise_center(&mut self, center: Complex<BigFloat>) {
+        if center.re.is_nan() || center.im.is_nan() {
+            return;
+        }
+        self.center = dvec2(center.re.to_f64(), center.im.to_f64());
+        self.precise_center = center;
+    }
+
+    // moves the precise center by `offset`, rounded to the bits the view needs
+    fn offset_center(&mut self, offset: DVec2) {
+        let bits = (self.center.abs().max_element().max(1.0) / self.pixel_size())
+            .log2()
+            .ceil()
+            .max(0.0) as usize
+            + CENTER_GUARD_BITS;
+        let moved = |value: &BigFloat, offset: f64| {
+            value.clone().with_precision(bits) + BigFloat::from_f64(offset)
+        };
+        self.set_precise_center(Complex::new(
+            moved(&self.precise_center.re, offset.x),
+            moved(&self.precise_center.im, offset.y),
+        ));
+    }
+
+    pub fn set_arbitrary_precision(&mut self, arbitrary_precision: bool) {
+        self.arbitrary_precision = arbitrary_precision;
+        self.scale = self.scale.max(self.min_scale());
+    }
+
+    pub fn get_arbitrary_precision(&self) -> bool {
+        self.arbitrary_precision
+    }
+
+    pub fn get_scale(&self) -> f64 {
+        self.scale
+    }
+
+    // the scale stays above `min_scale`, invalid scales are ignored
+    pub fn set_scale(&mut self, scale: f64) {
+        if scale.is_finite() && scale > 0.0 {
+            self.scale = scale.max(self.min_scale());
+        }
+    }
+
+    // the smallest scale around the current center
+    pub fn min_scale(&self) -> f64 {
+        let min_scale = if self.arbitrary_precision {
+            MIN_PRECISE_SCALE
+        } else {
+            MIN_SCALE
+        };
+        self.center.abs().max_element().max(1.0) * min_scale
+    }
+
+    // whether f32 is too coarse to tell the pixels apart, zooming deeper needs double-float
+    pub fn needs_deep_zoom(&self) -> bool {
+        self.scale < self.center.abs().max_element().max(1.0) * DEEP_ZOOM_SCALE
+    }
+
+    pub fn set_rotation(&mut self, rotation: f32) {
+        self.rotation = rotation;
+    }
+
+    pub fn get_rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    pub fn get_rotation_mut(&mut self) -> &mut f32 {
+        &mut self.rotation
+    }
+
+    // turns the plane by `angle` around the point at `position` on screen, which keeps its
+    // place, the rotation stays within -pi and pi
+    pub fn rotate(&mut self, position: Vec2, angle: f32) {
+        let local = self.local(position);
+        self.offset_center(local - DVec2::from_angle(angle as f64).rotate(local));
+        self.rotation = (self.rotation + angle + PI).rem_euclid(2.0 * PI) - PI;
+    }
+
+    pub fn set_aspect(&mut self, aspect: AspectPolicy) {
+        self.aspect = aspect;
+    }
+
+    pub fn get_aspect(&self) -> AspectPolicy {
+        self.aspect
+    }
+
+    pub fn set_size(&mut self, size: Vec2) {
+        self.size = size;
+    }
+
+    pub fn get_size(&self) -> Vec2 {
+        self.size
+    }
+
+    // the same view on a screen of another size
+    pub fn with_size(&self, size: Vec2) -> Self {
+        Self {
+            size,
+            ..self.clone()
+        }
+    }
+
+    // an empty screen still maps to a single pixel
+    fn size(&self) -> Vec2 {
+        self.size.max(vec2(1.0, 1.0))
+    }
+
+    // complex units per pixel
+    pub fn pixel_size(&self) -> f64 {
+        2.0 * self.scale / self.aspect.reference(self.size()) as f64
+    }
+
+    // half the span of the view along each screen axis, before rotating
+    pub fn extent(&self) -> DVec2 {
+        self.size().as_dvec2() * self.pixel_size() / 2.0
+    }
+
+    // the ranges round the center to f32 and blur deep zooms
+    pub fn real_range(&self) -> Vec2 {
+        let extent = self.extent();
+        dvec2(self.center.x - extent.x, self.center.x + extent.x).as_vec2()
+    }
+
+    pub fn imag_range(&self) -> Vec2 {
+        let extent = self.extent();
+        dvec2(self.center.y - extent.y, self.center.y + extent.y).as_vec2()
+    }
+
+    fn direction(&self) -> DVec2 {
+        DVec2::from_angle(self.rotation as f64)
+    }
+
+    // the offset of the point at `position` on screen from the center
+    fn local(&self, position: Vec2) -> DVec2 {
+        let local = (position - self.size() / 2.0).as_dvec2() * self.pixel_size();
+        self.direction().rotate(local)
+    }
+
+    pub fn screen_to_complex(&self, position: Vec2) -> DVec2 {
+        self.center + self.local(position)
+    }
+
+    // the offset from the center is taken in the precision of the center
+    pub fn complex_to_screen(&self, z: DVec2) -> Vec2 {
+        let offset = |z: f64, center: &BigFloat| (BigFloat::from_f64(z) - center.clone()).to_f64();
+        let offset = dvec2(
+            offset(z.x, &self.precise_center.re),
+            offset(z.y, &self.precise_center.im),
+        );
+        let local = DVec2::from_angle(-self.rotation as f64).rotate(offset);
+        (local / self.pixel_size()).as_vec2() + self.size() / 2.0
+    }
+
+    // moves the view by `offset` in the complex plane
+    pub fn pan(&mut self, offset: DVec2) {
+        self.offset_center(offset);
+    }
+
+    // moves the plane along with a drag of `delta` pixels on screen
+    pub fn drag(&mut self, delta: Vec2) {
+        self.offset_center(
+            -self
+                .direction()
+                .rotate(delta.as_dvec2() * self.pixel_size()),
+        );
+    }
+
+    // scales the view around the point at `position` on screen, which keeps its place,
+    // factors below 1 zoom in, the view stops shrinking at `min_scale`
+    pub fn zoom(&mut self, position: Vec2, factor: f32) {
+        let factor = factor as f64;
+        let offset = self.local(position) * (1.0 - factor);
+        let scale = self.scale * factor;
+        let too_small = scale < self.min_scale();
+        if !(offset.is_finite() && scale.is_finite() && scale > 0.0) || (factor < 1.0 && too_small)
+        {
+            return;
+        }
+        self.scale = scale;
+        self.offset_center(offset);
+    }
+}
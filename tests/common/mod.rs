@@ -0,0 +1,60 @@
+// fixtures shared by the test targets, which include this file through #[path]
+#![allow(dead_code)]
+
+use macroquad::math::{dvec2, vec2, Vec2};
+use newton_fractal::{CpuRenderer, Scene};
+
+// roots of z^3 - 1, in red, green and blue
+pub fn cubic_roots() -> Vec<Vec2> {
+    vec![
+        vec2(1.0, 0.0),
+        vec2(-0.5, 0.866_025_4),
+        vec2(-0.5, -0.866_025_4),
+    ]
+}
+
+pub fn rgb_colors() -> Vec<[f32; 3]> {
+    vec![[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]
+}
+
+// roots of z^2 - 1, in red and blue
+pub fn pair_roots() -> Vec<Vec2> {
+    vec![vec2(1.0, 0.0), vec2(-1.0, 0.0)]
+}
+
+pub fn pair_colors() -> Vec<[f32; 3]> {
+    vec![[1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]
+}
+
+// newton on z^3 - 2z + 2 cycles between 0 and 1 without reaching a root
+pub fn cycling_roots() -> Vec<Vec2> {
+    vec![
+        vec2(-1.769_292_4, 0.0),
+        vec2(0.884_646_2, 0.589_742_8),
+        vec2(0.884_646_2, -0.589_742_8),
+    ]
+}
+
+// views the unit square around the origin
+pub fn renderer(roots: Vec<Vec2>, colors: Vec<[f32; 3]>, max_iterations: u32) -> CpuRenderer {
+    CpuRenderer::new(
+        roots,
+        colors,
+        max_iterations,
+        vec2(-1.0, 1.0),
+        vec2(-1.0, 1.0),
+    )
+    .unwrap()
+}
+
+pub fn cubic_renderer(max_iterations: u32) -> CpuRenderer {
+    renderer(cubic_roots(), rgb_colors(), max_iterations)
+}
+
+pub fn pair_renderer(max_iterations: u32) -> CpuRenderer {
+    renderer(pair_roots(), pair_colors(), max_iterations)
+}
+
+pub fn scene(roots: Vec<Vec2>, colors: Vec<[f32; 3]>, max_iterations: u32) -> Scene {
+    Scene::new(&roots, &colors, max_iterations, dvec2(0.0, 0.0), 1.0)
+}
@@ -0,0 +1,57 @@
+#[path = "../common/mod.rs"]
+mod common;
+
+use newton_fractal::{cycle_color, CpuRenderer, Scene, MAX_CYCLE_PERIOD};
+use num_complex::Complex;
+
+// the cycle between 0 and 1 is superattracting
+fn cycling_renderer() -> CpuRenderer {
+    let mut renderer = common::renderer(common::cycling_roots(), common::rgb_colors(), 50);
+    renderer.set_max_cycle_period(4);
+    renderer
+}
+
+#[test]
+fn detects_two_cycle() {
+    let renderer = cycling_renderer();
+    let cycles = renderer.get_cycles();
+    assert_eq!(cycles.len(), 1);
+    let cycle = &cycles[0];
+    assert_eq!(cycle.period(), 2);
+    assert!(cycle.points[0].norm() < 1e-2);
+    assert!((cycle.points[1] - Complex::new(1.0, 0.0)).norm() < 1e-2);
+    assert!(cycle.multiplier.norm() < 0.1);
+
+    // too short a period misses it
+    assert!(renderer.find_cycles(1).is_empty());
+}
+
+#[test]
+fn cycle_basin_color() {
+    let mut renderer = cycling_renderer();
+    renderer.set_no_basin(0.1, [1.0, 1.0, 1.0]);
+    let [r, g, b] = cycle_color(0).map(|channel| (channel * 255.0).round() as u8);
+    assert_eq!(renderer.pixel_color(0, 0, 1, 1), [r, g, b, 255]);
+
+    renderer.set_max_cycle_period(0);
+    assert!(renderer.get_cycles().is_empty());
+    assert_eq!(renderer.pixel_color(0, 0, 1, 1), [255, 255, 255, 255]);
+}
+
+#[test]
+fn no_cycles_for_roots_of_unity() {
+    let mut renderer = common::cubic_renderer(50);
+    renderer.set_max_cycle_period(MAX_CYCLE_PERIOD);
+    assert!(renderer.get_cycles().is_empty());
+}
+
+#[test]
+fn cycle_scene() {
+    let mut scene = common::scene(common::pair_roots(), common::pair_colors(), 30);
+    scene.max_cycle_period = 3;
+    let loaded = Scene::from_json(&scene.to_json()).unwrap();
+    assert_eq!(loaded.cpu_renderer(8, 8).unwrap().get_max_cycle_period(), 3);
+
+    scene.max_cycle_period = MAX_CYCLE_PERIOD + 1;
+    assert!(scene.cpu_renderer(8, 8).is_err());
+}
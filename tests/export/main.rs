@@ -0,0 +1,149 @@
+#[path = "../common/mod.rs"]
+mod common;
+
+use macroquad::math::vec2;
+use newton_fractal::{
+    export_png, read_png_parameters, BigFloat, CpuRenderer, Householder, Method, Real,
+};
+use num_complex::Complex;
+use std::{env, fs};
+
+#[test]
+fn png_parameters_roundtrip() {
+    let mut renderer = CpuRenderer::new(
+        vec![vec2(1.0, 0.0), vec2(-0.5, 0.866_025_4), vec2(-0.5, -0.3)],
+        vec![[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.25, 0.5, 1.0]],
+        42,
+        vec2(-1.5, 2.0),
+        vec2(-0.125, 1.0),
+    )
+    .unwrap();
+    renderer.set_method(Method::Householder(Householder { order: 4 }));
+    renderer.set_relaxation(Complex::new(0.75, -0.25));
+    renderer.set_tolerance(1e-3);
+    renderer.set_no_basin(0.5, [0.25, 0.5, 1.0]);
+    renderer.set_max_cycle_period(2);
+    let path = env::temp_dir().join("newton_fractal_png_parameters_roundtrip.png");
+    export_png(&renderer, &path, 32, 16).unwrap();
+
+    let decoder = png::Decoder::new(fs::File::open(&path).unwrap());
+    let reader = decoder.read_info().unwrap();
+    assert_eq!(reader.info().width, 32);
+    assert_eq!(reader.info().height, 16);
+
+    let loaded = read_png_parameters(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.get_roots(), renderer.get_roots());
+    assert_eq!(loaded.get_colors(), renderer.get_colors());
+    assert_eq!(loaded.get_max_iterations(), 42);
+    assert_eq!(loaded.get_real_range(), vec2(-1.5, 2.0));
+    assert_eq!(loaded.get_imag_range(), vec2(-0.125, 1.0));
+    assert_eq!(loaded.get_method(), renderer.get_method());
+    assert_eq!(loaded.get_relaxation(), Complex::new(0.75, -0.25));
+    assert_eq!(loaded.get_tolerance(), 1e-3);
+    assert_eq!(loaded.get_convergence_radius(), 0.5);
+    assert_eq!(loaded.get_no_basin_color(), [0.25, 0.5, 1.0]);
+    assert_eq!(loaded.get_max_cycle_period(), 2);
+}
+
+#[test]
+fn png_deep_view_roundtrip() {
+    let mut renderer = common::pair_renderer(20);
+    let center = Complex::new(0.123_456_789_012_345_67, -1.0e-3);
+    renderer.set_view(center, Complex::new(2.0e-20, 1.0e-20));
+    renderer.set_arbitrary_precision(true);
+    let path = env::temp_dir().join("newton_fractal_png_deep_view_roundtrip.png");
+    export_png(&renderer, &path, 4, 2).unwrap();
+
+    let loaded = read_png_parameters(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    // the f32 ranges can't hold the view
+    assert_eq!(loaded.get_center(), center);
+    assert_eq!(loaded.get_extent(), Complex::new(2.0e-20, 1.0e-20));
+    assert!(loaded.get_arbitrary_precision());
+
+    // neither can f64 hold a center closer to 1 than 1e-20
+    let center: Complex<BigFloat> = Complex::new(
+        "1.000000000000000000000000123".parse().unwrap(),
+        BigFloat::from_f64(0.5),
+    );
+    renderer.set_precise_view(center.clone(), Complex::new(1.0e-25, 1.0e-25));
+    export_png(&renderer, &path, 4, 2).unwrap();
+    let loaded = read_png_parameters(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    let offset = loaded.get_precise_center().re.clone() - BigFloat::from_f64(1.0);
+    assert!((offset.to_f64() - 1.23e-25).abs() < 1e-27);
+    assert_eq!(loaded.get_precise_center().im.to_f64(), 0.5);
+}
+
+#[test]
+fn png_without_parameters() {
+    let path = env::temp_dir().join("newton_fractal_png_without_parameters.png");
+    let mut encoder = png::Encoder::new(fs::File::create(&path).unwrap(), 1, 1);
+    encoder.set_color(png::ColorType::Rgba);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&[0, 0, 0, 255]).unwrap();
+    writer.finish().unwrap();
+
+    let loaded = read_png_parameters(&path);
+    fs::remove_file(&path).unwrap();
+    assert!(loaded.is_err());
+}
+
+#[test]
+fn png_from_before_the_options() {
+    let path = env::temp_dir().join("newton_fractal_png_from_before_the_options.png");
+    let mut encoder = png::Encoder::new(fs::File::create(&path).unwrap(), 1, 1);
+    encoder.set_color(png::ColorType::Rgba);
+    for (keyword, text) in [
+        ("Software", "newton_fractal"),
+        ("Roots", "1 0;-1 0"),
+        ("Colors", "1 0 0;0 0 1"),
+        ("Iterations", "20"),
+        ("RealRange", "-1 1"),
+        ("ImagRange", "-1 1"),
+    ] {
+        encoder
+            .add_text_chunk(keyword.to_owned(), text.to_owned())
+            .unwrap();
+    }
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&[0, 0, 0, 255]).unwrap();
+    writer.finish().unwrap();
+
+    let loaded = read_png_parameters(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.get_method(), Method::default());
+    // rendered the way it was exported, every iteration run and every pixel in a basin
+    assert_eq!(loaded.get_tolerance(), 0.0);
+    assert_eq!(loaded.get_convergence_radius(), f32::INFINITY);
+}
+
+#[test]
+fn png_fine_zoom_is_not_blocky() {
+    // every basin of z^3 - z meets around the pole of its newton map
+    let roots = vec![vec2(-1.0, 0.0), vec2(0.0, 0.0), vec2(1.0, 0.0)];
+    let mut renderer = common::renderer(roots, common::rgb_colors(), 200);
+    renderer.set_view(
+        Complex::new(1.0 / 3.0f64.sqrt(), 0.0),
+        Complex::new(1e-6, 1e-6),
+    );
+    let path = env::temp_dir().join("newton_fractal_png_fine_zoom_is_not_blocky.png");
+    export_png(&renderer, &path, 128, 128).unwrap();
+
+    let decoder = png::Decoder::new(fs::File::open(&path).unwrap());
+    let mut reader = decoder.read_info().unwrap();
+    let mut bytes = vec![0; reader.output_buffer_size()];
+    reader.next_frame(&mut bytes).unwrap();
+    fs::remove_file(&path).unwrap();
+    // f32 steps by about four pixels along the real axis here, so most columns would
+    // repeat the one before
+    let column = |x: usize| {
+        bytes
+            .chunks_exact(128 * 4)
+            .map(|row| &row[x * 4..x * 4 + 4])
+            .collect::<Vec<_>>()
+    };
+    let repeated = (1..128).filter(|&x| column(x) == column(x - 1)).count();
+    assert!(repeated < 64, "{} columns repeat", repeated);
+}
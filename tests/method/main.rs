@@ -0,0 +1,78 @@
+#[path = "../common/mod.rs"]
+mod common;
+
+use macroquad::math::vec2;
+use newton_fractal::{Halley, Householder, IterationMethod, Method, Newton, MAX_HOUSEHOLDER_ORDER};
+use num_complex::Complex;
+
+#[test]
+fn householder_low_orders() {
+    let z = Complex::new(0.3, -1.2);
+    let d = [
+        Complex::new(1.5, 0.5),
+        Complex::new(-2.0, 1.0),
+        Complex::new(0.25, 3.0),
+    ];
+    let newton = Householder { order: 1 }.step(z, &d);
+    assert!((newton - Newton.step(z, &d)).norm() < 1e-5);
+    let halley = Householder { order: 2 }.step(z, &d);
+    assert!((halley - Halley.step(z, &d)).norm() < 1e-5);
+}
+
+#[test]
+fn methods_converge() {
+    let mut methods = Method::ALL.to_vec();
+    methods.push(Method::Householder(Householder {
+        order: MAX_HOUSEHOLDER_ORDER,
+    }));
+    for method in methods {
+        let mut renderer = common::cubic_renderer(50);
+        renderer.set_method(method);
+        for (i, root) in renderer.get_roots().iter().enumerate() {
+            let start = Complex::new(root.re as f64, root.im as f64) * 0.9;
+            let z = renderer.iterate(start + Complex::new(0.02, 0.03));
+            assert_eq!(renderer.closest_root(z), Some(i), "{}", method);
+            assert!((z - root).norm() < 1e-4, "{}", method);
+        }
+    }
+}
+
+#[test]
+fn schroeder_double_root() {
+    // newton only converges linearly on the double root at 1
+    let roots = vec![vec2(1.0, 0.0), vec2(1.0, 0.0), vec2(-1.0, 0.0)];
+    let error = |method| {
+        let mut renderer = common::renderer(roots.clone(), common::rgb_colors(), 4);
+        renderer.set_method(method);
+        (renderer.iterate(Complex::new(1.5, 0.2)) - Complex::new(1.0, 0.0)).norm()
+    };
+    assert!(error(Method::Schroeder) < 1e-4);
+    assert!(error(Method::Newton) > 1e-3);
+}
+
+#[test]
+fn relaxed_newton() {
+    let z = Complex::new(0.5, 0.5);
+    let mut renderer = common::pair_renderer(1);
+    let newton = renderer.iterate(Complex::new(0.5, 0.5));
+    let relaxation = Complex::new(0.5, 0.25);
+    renderer.set_relaxation(relaxation);
+    let relaxed = renderer.iterate(Complex::new(0.5, 0.5));
+    // z - a p(z) / p'(z)
+    assert!((relaxed - (z + relaxation * (newton - z))).norm() < 1e-6);
+    assert!((relaxed - (z - relaxation * (z * z - 1.0) / (2.0 * z))).norm() < 1e-6);
+}
+
+#[test]
+fn method_names() {
+    for method in Method::ALL {
+        assert_eq!(method.to_string().parse::<Method>(), Ok(method));
+    }
+    assert_eq!(
+        "householder:5".parse::<Method>(),
+        Ok(Method::Householder(Householder { order: 5 }))
+    );
+    assert!("householder:0".parse::<Method>().is_err());
+    assert!("halley:2".parse::<Method>().is_err());
+    assert!("secant".parse::<Method>().is_err());
+}
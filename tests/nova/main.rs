@@ -0,0 +1,76 @@
+#[path = "../common/mod.rs"]
+mod common;
+
+use newton_fractal::{CpuRenderer, Nova, OrbitState, Scene};
+use num_complex::Complex;
+use std::collections::HashSet;
+
+fn nova_renderer(nova: Nova, constant: Complex<f32>) -> CpuRenderer {
+    let mut renderer = common::cubic_renderer(100);
+    renderer.set_nova(nova, constant);
+    renderer
+}
+
+#[test]
+fn julia_without_constant() {
+    let renderer = nova_renderer(Nova::Julia, Complex::new(0.0, 0.0));
+    let orbit = renderer.nova_orbit(Complex::new(0.8, 0.1));
+    assert_eq!(orbit.state, OrbitState::Converged);
+    assert!((orbit.z - Complex::new(1.0, 0.0)).norm() < 1e-4);
+    assert!(orbit.iterations > 1 && orbit.iterations < 100);
+}
+
+#[test]
+fn fixed_point_colors() {
+    // however many steps they take, orbits settling on the same point get the same color
+    let mut renderer = nova_renderer(Nova::Julia, Complex::new(0.0, 0.0));
+    renderer.set_view(Complex::new(1.0, 0.0), Complex::new(0.3, 0.3));
+    let mut iterations = HashSet::new();
+    for y in 0..8 {
+        for x in 0..8 {
+            let orbit = renderer.nova_orbit(renderer.pixel_to_complex(x, y, 8, 8));
+            assert_eq!(orbit.state, OrbitState::Converged);
+            assert!((orbit.z - Complex::new(1.0, 0.0)).norm() < 1e-4);
+            iterations.insert(orbit.iterations);
+            assert_eq!(renderer.pixel_color(x, y, 8, 8), [255, 0, 0, 255]);
+        }
+    }
+    assert!(iterations.len() >= 3);
+}
+
+#[test]
+fn mandelbrot_fixed_point() {
+    // far from the roots the newton map of z^3 - 1 is about 2z/3, so z settles near 3c
+    let renderer = nova_renderer(Nova::Mandelbrot, Complex::new(0.0, 0.0));
+    let orbit = renderer.nova_orbit(Complex::new(50.0, 20.0));
+    assert_eq!(orbit.state, OrbitState::Converged);
+    assert!((orbit.z - Complex::new(150.0, 60.0)).norm() < 0.1);
+}
+
+#[test]
+fn escaping_orbit() {
+    // a relaxation of -2 turns the newton step into z + 2z/3, pushing orbits outwards
+    let mut renderer = nova_renderer(Nova::Julia, Complex::new(0.0, 0.0));
+    renderer.set_relaxation(Complex::new(-2.0, 0.0));
+    let orbit = renderer.nova_orbit(Complex::new(0.75, 0.75));
+    assert_eq!(orbit.state, OrbitState::Escaped);
+
+    // escaped pixels are shaded by the iterations it took them to leave
+    let z = renderer.pixel_to_complex(7, 7, 8, 8);
+    assert_eq!(z, Complex::new(0.875, 0.875));
+    let shade = renderer.nova_orbit(z).iterations as f32 / 100.0;
+    let byte = (shade * 255.0).round() as u8;
+    assert_eq!(renderer.pixel_color(7, 7, 8, 8), [byte, byte, byte, 255]);
+}
+
+#[test]
+fn nova_scene() {
+    let mut scene = common::scene(common::pair_roots(), common::pair_colors(), 30);
+    scene.nova = Nova::Mandelbrot;
+    scene.nova_constant = [0.25, -0.5];
+    let loaded = Scene::from_json(&scene.to_json()).unwrap();
+    assert_eq!(loaded, scene);
+    let renderer = loaded.cpu_renderer(8, 8).unwrap();
+    assert_eq!(renderer.get_nova(), Nova::Mandelbrot);
+    assert_eq!(renderer.get_nova_constant(), Complex::new(0.25, -0.5));
+}
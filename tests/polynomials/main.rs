@@ -68,3 +68,37 @@ fn polynomial_degree() {
     let poly = Polynomial::new(vec![Complex::new(1.0, 0.0)]);
     assert_eq!(poly.degree(), 0);
 }
+
+#[test]
+fn double_precision_coefficients() {
+    // the roots of unity expand to z^n - 1, every middle coefficient vanishes
+    let n = 24;
+    let roots: Vec<Complex<f64>> = (0..n)
+        .map(|k| Complex::from_polar(1.0, std::f64::consts::TAU * k as f64 / n as f64))
+        .collect();
+    let double = Polynomial::<f64>::from_roots(&roots);
+    let single = Polynomial::<f32>::from_roots(
+        &roots
+            .iter()
+            .map(|root| Complex::new(root.re as f32, root.im as f32))
+            .collect::<Vec<_>>(),
+    );
+    let double_error = double.get_coefficients()[1..n]
+        .iter()
+        .map(|coeff| coeff.norm())
+        .fold(0.0, f64::max);
+    let single_error = single.get_coefficients()[1..n]
+        .iter()
+        .map(|coeff| coeff.norm() as f64)
+        .fold(0.0, f64::max);
+    assert!(double_error < 1e-9);
+    assert!(single_error > 1e3 * double_error);
+
+    // narrowing rounds each coefficient, widening back is exact
+    let narrowed = Polynomial::<f32>::from(&double);
+    assert_eq!(narrowed.degree(), n);
+    assert!((narrowed.get_coefficients()[n] - Complex::new(-1.0, 0.0)).norm() < 1e-6);
+    assert_eq!(Polynomial::<f64>::from(&narrowed).cast::<f32>(), narrowed);
+    let z = Complex::new(0.3, -0.8);
+    assert!((double.evaluate(z) - (z.powi(n as i32) - 1.0)).norm() < 1e-9);
+}
@@ -0,0 +1,122 @@
+#[path = "../common/mod.rs"]
+mod common;
+
+use common::cubic_renderer;
+use macroquad::{math::vec2, texture::Image};
+use newton_fractal::{BigFloat, Method, Nova, Real};
+use num_complex::Complex;
+use std::collections::HashSet;
+
+fn colors(image: &Image) -> HashSet<[u8; 4]> {
+    image.get_image_data().iter().copied().collect()
+}
+
+#[test]
+fn big_float_arithmetic() {
+    let third = BigFloat::from_f64(1.0).with_precision(200) / BigFloat::from_f64(3.0);
+    assert_eq!(third.precision(), 200);
+    assert_eq!(third.to_f64(), 1.0 / 3.0);
+    // exact values keep every bit, quotients of them are rounded like an f64
+    let tiny = BigFloat::from_f64(1.0) + BigFloat::from_f64(1e-300);
+    assert_eq!(tiny.precision(), 0);
+    assert_eq!((tiny - BigFloat::from_f64(1.0)).to_f64(), 1e-300);
+    assert_eq!(
+        (BigFloat::from_f64(1.0) / BigFloat::from_f64(3.0)).precision(),
+        53
+    );
+
+    assert!((BigFloat::from_f64(1.0) / BigFloat::from_f64(0.0)).is_nan());
+    assert!(BigFloat::from_f64(f64::INFINITY).is_nan());
+    assert!((BigFloat::NAN + BigFloat::from_f64(1.0)).to_f64().is_nan());
+}
+
+#[test]
+fn big_float_decimals() {
+    let center: BigFloat = "1.00000000000000000001".parse().unwrap();
+    assert_eq!(center.to_f64(), 1.0);
+    assert!(center.to_string().starts_with("1.00000000000000000001"));
+    assert_eq!(center.to_string().parse::<BigFloat>().unwrap(), center);
+    let tiny = (center - BigFloat::from_f64(1.0)).to_f64();
+    assert!((tiny - 1e-20).abs() < 1e-21);
+
+    assert_eq!("-2.5".parse::<BigFloat>().unwrap().to_f64(), -2.5);
+    assert_eq!("1e-30".parse::<BigFloat>().unwrap().to_f64(), 1e-30);
+    // f64 values read back exactly and print as f64 does
+    for value in [0.1, -0.743_643_887_037_158_7, 1e-30, 123.456, 1e30] {
+        let text = BigFloat::from_f64(value).to_string();
+        assert_eq!(text, value.to_string());
+        assert_eq!(text.parse::<BigFloat>().unwrap(), BigFloat::from_f64(value));
+    }
+    assert_eq!(BigFloat::NAN.to_string(), "NaN");
+    assert!("one".parse::<BigFloat>().is_err());
+}
+
+#[test]
+fn precision_follows_the_view() {
+    let mut renderer = cubic_renderer(30);
+    let shallow = renderer.precision(64, 64);
+    renderer.set_view(Complex::new(-0.5, 0.25), Complex::new(1e-20, 1e-20));
+    let deep = renderer.precision(64, 64);
+    assert!(deep > shallow + 32);
+    assert!(renderer.precision(1024, 1024) > deep);
+}
+
+#[test]
+fn shallow_view_matches() {
+    for method in [Method::Newton, Method::Halley] {
+        let mut renderer = cubic_renderer(30);
+        renderer.set_method(method);
+        renderer.set_smooth(true);
+        let single = renderer.render(24, 16);
+        renderer.set_arbitrary_precision(true);
+        assert_eq!(single.bytes, renderer.render(24, 16).bytes);
+    }
+
+    let mut renderer = cubic_renderer(30);
+    renderer.set_nova(Nova::Mandelbrot, Complex::new(0.0, 0.0));
+    let single = renderer.render(8, 8);
+    renderer.set_arbitrary_precision(true);
+    assert_eq!(single.bytes, renderer.render(8, 8).bytes);
+}
+
+#[test]
+fn deep_zoom_resolves_boundary() {
+    // the basins of 0 and 2 meet on the line through 1, newton keeps orbits on it and only
+    // slowly pushes the ones beside it apart
+    let roots = vec![vec2(0.0, 0.0), vec2(2.0, 0.0)];
+    let mut renderer = common::renderer(roots, common::pair_colors(), 200);
+    renderer.set_view(Complex::new(1.0, 0.5), Complex::new(1e-20, 1e-20));
+    // f64 puts every pixel on the line
+    assert_eq!(colors(&renderer.render(16, 16)).len(), 1);
+
+    renderer.set_arbitrary_precision(true);
+    assert!(renderer.precision(16, 16) > 64);
+    assert_eq!(colors(&renderer.render(16, 16)).len(), 2);
+}
+
+#[test]
+fn center_beyond_f64() {
+    let roots = vec![vec2(0.0, 0.0), vec2(2.0, 0.0)];
+    let mut renderer = common::renderer(roots, common::pair_colors(), 200);
+    // the boundary at 1 is a quarter of the way across, f64 rounds the center onto it
+    let center = Complex::new(
+        "1.00000000000000000001".parse().unwrap(),
+        BigFloat::from_f64(0.5),
+    );
+    renderer.set_precise_view(center, Complex::new(2e-20, 2e-20));
+    assert_eq!(renderer.get_center(), Complex::new(1.0, 0.5));
+    let double = renderer.render(16, 16);
+    assert_eq!(colors(&double).len(), 1);
+
+    renderer.set_arbitrary_precision(true);
+    let image = renderer.render(16, 16);
+    assert_ne!(image.bytes, double.bytes);
+    for (i, pixel) in image.get_image_data().iter().enumerate() {
+        let expected = if i % 16 < 4 {
+            [255, 0, 0, 255]
+        } else {
+            [0, 0, 255, 255]
+        };
+        assert_eq!(*pixel, expected, "pixel {}", i);
+    }
+}
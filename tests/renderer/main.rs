@@ -0,0 +1,256 @@
+#[path = "../common/mod.rs"]
+mod common;
+
+use macroquad::math::vec2;
+use newton_fractal::{
+    CancelToken, CpuRenderer, FractalError, Method, OrbitState, Shading, TileOptions,
+};
+use num_complex::Complex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn cubic_renderer() -> CpuRenderer {
+    common::cubic_renderer(30)
+}
+
+#[test]
+fn mismatched_colors() {
+    let renderer = CpuRenderer::new(
+        vec![vec2(1.0, 0.0), vec2(-1.0, 0.0)],
+        vec![[1.0, 0.0, 0.0]],
+        30,
+        vec2(-1.0, 1.0),
+        vec2(-1.0, 1.0),
+    );
+    assert!(matches!(
+        renderer,
+        Err(FractalError::MismatchedColors {
+            roots: 2,
+            colors: 1
+        })
+    ));
+
+    let renderer = CpuRenderer::new(vec![], vec![], 30, vec2(-1.0, 1.0), vec2(-1.0, 1.0));
+    assert!(matches!(renderer, Err(FractalError::NoRoots)));
+}
+
+#[test]
+fn closest_root_ties() {
+    let renderer = cubic_renderer();
+    assert_eq!(renderer.closest_root(Complex::new(0.9, 0.0)), Some(0));
+    assert_eq!(renderer.closest_root(Complex::new(-0.5, -0.8)), Some(2));
+    // the origin is equidistant from every root, the shader falls back to the last one
+    assert_eq!(renderer.closest_root(Complex::new(0.0, 0.0)), Some(2));
+    assert_eq!(renderer.closest_root(Complex::new(f32::NAN, 0.0)), Some(2));
+}
+
+#[test]
+fn halley_method() {
+    let newton = cubic_renderer();
+    let mut halley = cubic_renderer();
+    halley.set_method(Method::Halley);
+    assert_eq!(halley.get_method(), Method::Halley);
+    for (i, root) in newton.get_roots().iter().enumerate() {
+        let z = Complex::new(root.re as f64, root.im as f64) * 0.8 + Complex::new(0.05, 0.05);
+        assert_eq!(halley.closest_root(halley.iterate(z)), Some(i));
+        assert!((halley.iterate(z) - root).norm() < 1e-5);
+    }
+
+    // cubic convergence gets much closer to the root in a few steps than newton
+    let few_steps = |method| {
+        let mut renderer = common::pair_renderer(3);
+        renderer.set_method(method);
+        (renderer.iterate(Complex::new(3.0, 0.5)) - Complex::new(1.0, 0.0)).norm()
+    };
+    assert!(few_steps(Method::Halley) * 100.0 < few_steps(Method::Newton));
+}
+
+#[test]
+fn early_exit() {
+    let mut renderer = cubic_renderer();
+    let z = Complex::new(0.9, 0.1);
+    let orbit = renderer.orbit(z);
+    assert_eq!(orbit.state, OrbitState::Converged);
+    assert!(orbit.iterations < 10);
+    assert!((orbit.z - Complex::new(1.0, 0.0)).norm() < renderer.get_tolerance());
+
+    // a tolerance of 0 runs every iteration and lands on the same root
+    renderer.set_tolerance(0.0);
+    let orbit = renderer.orbit(z);
+    assert_eq!(orbit.state, OrbitState::Bounded);
+    assert_eq!(orbit.iterations, 30);
+    assert_eq!(renderer.closest_root(orbit.z), Some(0));
+}
+
+#[test]
+fn iteration_shading() {
+    let mut renderer = cubic_renderer();
+    let (x, y) = (15, 10);
+    let orbit = renderer.orbit(renderer.pixel_to_complex(x, y, 16, 16));
+    assert_eq!(renderer.closest_root(orbit.z), Some(0));
+    assert_eq!(renderer.pixel_color(x, y, 16, 16), [255, 0, 0, 255]);
+
+    let linear = 1.0 - orbit.iterations as f32 / 30.0;
+    renderer.set_shading(Shading::Linear, 1.0);
+    let red = (linear * 255.0).round() as u8;
+    assert_eq!(renderer.pixel_color(x, y, 16, 16), [red, 0, 0, 255]);
+    // a gamma of 1 is linear, larger gammas darken
+    renderer.set_shading(Shading::Gamma, 1.0);
+    assert_eq!(renderer.pixel_color(x, y, 16, 16), [red, 0, 0, 255]);
+    renderer.set_shading(Shading::Gamma, 2.0);
+    assert!(renderer.pixel_color(x, y, 16, 16)[0] < red);
+
+    assert_eq!(Shading::Log.brightness(0.0, 30, 1.0), 1.0);
+    assert_eq!(Shading::Log.brightness(30.0, 30, 1.0), 0.0);
+    assert!(Shading::Log.brightness(3.0, 30, 1.0) < Shading::Linear.brightness(3.0, 30, 1.0));
+}
+
+#[test]
+fn smooth_iterations() {
+    let mut renderer = cubic_renderer();
+    renderer.set_tolerance(1e-3);
+    let root = Complex::new(1.0, 0.0);
+    let samples: Vec<(u32, f32)> = (0..200)
+        .map(|i| {
+            let orbit = renderer.orbit(Complex::new(1.2 + i as f64 * 0.01, 0.3));
+            assert_eq!(renderer.closest_root(orbit.z), Some(0));
+            let smooth = renderer.smooth_iterations(&orbit, root);
+            assert!(smooth <= orbit.iterations as f32);
+            assert!(smooth >= orbit.iterations as f32 - 1.0);
+            (orbit.iterations, smooth)
+        })
+        .collect();
+    // the integer count jumps between bands while the fractional one changes gradually
+    assert!(samples.windows(2).any(|pair| pair[0].0 != pair[1].0));
+    assert!(samples
+        .windows(2)
+        .all(|pair| (pair[0].1 - pair[1].1).abs() < 0.25));
+}
+
+#[test]
+fn no_basin() {
+    let mut renderer = common::renderer(common::cycling_roots(), common::rgb_colors(), 30);
+    renderer.set_no_basin(0.1, [1.0, 1.0, 1.0]);
+    let orbit = renderer.orbit(Complex::new(0.0, 0.0));
+    assert!(renderer.closest_root(orbit.z).is_some());
+    assert_eq!(renderer.basin(orbit.z), None);
+    assert_eq!(renderer.pixel_color(0, 0, 1, 1), [255, 255, 255, 255]);
+
+    let orbit = renderer.orbit(Complex::new(-1.5, 0.0));
+    assert_eq!(renderer.basin(orbit.z), Some(0));
+}
+
+#[test]
+fn pixel_mapping() {
+    let renderer = cubic_renderer();
+    assert_eq!(
+        renderer.pixel_to_complex(0, 0, 2, 2),
+        Complex::new(-0.5, -0.5)
+    );
+    assert_eq!(
+        renderer.pixel_to_complex(1, 1, 2, 2),
+        Complex::new(0.5, 0.5)
+    );
+}
+
+#[test]
+fn render_basins() {
+    let renderer = cubic_renderer();
+    let image = renderer.render(64, 48);
+    assert_eq!(image.width, 64);
+    assert_eq!(image.height, 48);
+    assert_eq!(image.bytes.len(), 64 * 48 * 4);
+
+    // right side of the real axis converges to 1
+    let pixel = &image.get_image_data()[24 * 64 + 63];
+    assert_eq!(pixel, &[255, 0, 0, 255]);
+    // the imaginary range starts at the top of the image
+    let pixel = &image.get_image_data()[0];
+    assert_eq!(pixel, &[0, 0, 255, 255]);
+}
+
+#[test]
+fn tiled_render_matches() {
+    let renderer = cubic_renderer();
+    let reference = renderer.render_tiled(
+        50,
+        30,
+        TileOptions {
+            tile_size: 64,
+            threads: 1,
+        },
+        |_, _| {},
+        &CancelToken::new(),
+    );
+    let tiled = renderer.render_tiled(
+        50,
+        30,
+        TileOptions {
+            tile_size: 7,
+            threads: 4,
+        },
+        |_, _| {},
+        &CancelToken::new(),
+    );
+    assert_eq!(reference.unwrap().bytes, tiled.unwrap().bytes);
+}
+
+#[test]
+fn tiled_render_progress() {
+    let renderer = cubic_renderer();
+    let calls = AtomicUsize::new(0);
+    let image = renderer.render_tiled(
+        20,
+        10,
+        TileOptions {
+            tile_size: 8,
+            threads: 3,
+        },
+        |done, total| {
+            assert_eq!(total, 6);
+            assert!(done <= total);
+            calls.fetch_add(1, Ordering::Relaxed);
+        },
+        &CancelToken::new(),
+    );
+    assert!(image.is_some());
+    assert_eq!(calls.load(Ordering::Relaxed), 6);
+}
+
+#[test]
+fn tiled_render_cancel() {
+    let renderer = cubic_renderer();
+    let cancel = CancelToken::new();
+    let image = renderer.render_tiled(
+        64,
+        64,
+        TileOptions {
+            tile_size: 8,
+            threads: 2,
+        },
+        |done, _| {
+            if done == 1 {
+                cancel.cancel();
+            }
+        },
+        &cancel,
+    );
+    assert!(image.is_none());
+
+    // cancelling after the last tile loses nothing
+    let cancel = CancelToken::new();
+    let image = renderer.render_tiled(
+        16,
+        16,
+        TileOptions {
+            tile_size: 8,
+            threads: 2,
+        },
+        |done, total| {
+            if done == total {
+                cancel.cancel();
+            }
+        },
+        &cancel,
+    );
+    assert!(image.is_some());
+}
@@ -0,0 +1,210 @@
+#[path = "../common/mod.rs"]
+mod common;
+
+use macroquad::math::{dvec2, vec2};
+use newton_fractal::{
+    scene_from_url_params, scene_to_url_params, url_query, AspectPolicy, BigFloat, FractalError,
+    Method, Real, Scene, SceneError, SCENE_VERSION,
+};
+
+fn cubic_scene() -> Scene {
+    common::scene(common::cubic_roots(), common::rgb_colors(), 30)
+}
+
+#[test]
+fn scene_roundtrip() {
+    let scene = cubic_scene();
+    assert_eq!(scene.version, SCENE_VERSION);
+    let loaded = Scene::from_json(&scene.to_json()).unwrap();
+    assert_eq!(loaded, scene);
+}
+
+#[test]
+fn scene_version_1_ranges() {
+    let json = r#"{
+        "version": 1,
+        "roots": [[1.0, 0.0], [-1.0, 0.0]],
+        "colors": [[1.0, 0.0, 0.0], [0.0, 0.0, 1.0]],
+        "max_iterations": 30,
+        "real_range": [-2.0, 1.0],
+        "imag_range": [-1.0, 1.0]
+    }"#;
+    let scene = Scene::from_json(json).unwrap();
+    assert_eq!(scene.version, SCENE_VERSION);
+    assert_eq!(scene.get_center(), dvec2(-0.5, 0.0));
+    assert_eq!(scene.scale, 1.5);
+    assert_eq!(scene.aspect, AspectPolicy::Fit);
+
+    let flat = json.replace("[-1.0, 1.0]", "[1.0, 1.0]");
+    assert!(matches!(
+        Scene::from_json(&flat),
+        Err(SceneError::InvalidRange("imaginary"))
+    ));
+}
+
+#[test]
+fn scene_precise_center() {
+    let mut scene = cubic_scene();
+    let center: BigFloat = "1.00000000000000000001".parse().unwrap();
+    assert_eq!(center.to_f64(), 1.0);
+    scene.center = [center.clone(), BigFloat::from_f64(0.5)];
+    scene.scale = 1e-20;
+    scene.arbitrary_precision = true;
+    let loaded = Scene::from_json(&scene.to_json()).unwrap();
+    assert_eq!(loaded, scene);
+    assert_eq!(loaded.center[0], center);
+
+    // the viewport and renderer of the scene keep it too
+    let viewport = loaded.viewport(vec2(16.0, 16.0));
+    assert_eq!(viewport.get_precise_center().re, center);
+    assert!(viewport.get_arbitrary_precision());
+    let renderer = loaded.cpu_renderer(16, 16).unwrap();
+    assert_eq!(renderer.get_precise_center().re, center);
+    assert!(renderer.get_arbitrary_precision());
+}
+
+#[test]
+fn scene_version_2_center() {
+    let json = r#"{
+        "version": 2,
+        "roots": [[1.0, 0.0], [-1.0, 0.0]],
+        "colors": [[1.0, 0.0, 0.0], [0.0, 0.0, 1.0]],
+        "max_iterations": 30,
+        "center": [-0.743643887037158, 0.131825904205312],
+        "scale": 0.001
+    }"#;
+    let scene = Scene::from_json(json).unwrap();
+    assert_eq!(scene.version, SCENE_VERSION);
+    assert_eq!(
+        scene.get_center(),
+        dvec2(-0.743643887037158, 0.131825904205312)
+    );
+    assert!(!scene.arbitrary_precision);
+    assert!(scene.to_json().contains("\"-0.743643887037158\""));
+}
+
+#[test]
+fn scene_mismatched_colors() {
+    let json = r#"{
+        "version": 1,
+        "roots": [[1.0, 0.0], [-1.0, 0.0]],
+        "colors": [[1.0, 0.0, 0.0]],
+        "max_iterations": 30,
+        "real_range": [-1.0, 1.0],
+        "imag_range": [-1.0, 1.0]
+    }"#;
+    assert!(matches!(
+        Scene::from_json(json),
+        Err(SceneError::Fractal(FractalError::MismatchedColors {
+            roots: 2,
+            colors: 1
+        }))
+    ));
+}
+
+#[test]
+fn scene_method() {
+    let scene = cubic_scene();
+    let json = scene.to_json();
+    assert!(json.contains("\"method\": \"newton\""));
+    // scenes saved before the method was selectable use newton
+    let legacy = json.replace(",\n  \"method\": \"newton\"", "");
+    assert!(!legacy.contains("method"));
+    assert_eq!(Scene::from_json(&legacy).unwrap(), scene);
+
+    let halley = Scene::from_json(&json.replace("newton", "halley")).unwrap();
+    assert_eq!(halley.method, Method::Halley);
+    assert!(Scene::from_json(&json.replace("newton", "secant")).is_err());
+}
+
+#[test]
+fn scene_validation() {
+    let mut scene = cubic_scene();
+    scene.version = SCENE_VERSION + 1;
+    assert!(matches!(
+        scene.validate(),
+        Err(SceneError::UnsupportedVersion(_))
+    ));
+
+    let mut scene = cubic_scene();
+    scene.roots.clear();
+    scene.colors.clear();
+    assert!(matches!(
+        scene.validate(),
+        Err(SceneError::Fractal(FractalError::NoRoots))
+    ));
+
+    let mut scene = cubic_scene();
+    scene.colors[1] = [0.0, 255.0, 0.0];
+    assert!(matches!(scene.validate(), Err(SceneError::InvalidColor(1))));
+
+    let mut scene = cubic_scene();
+    scene.scale = 0.0;
+    assert!(matches!(
+        scene.validate(),
+        Err(SceneError::InvalidParameter("scale"))
+    ));
+
+    assert!(matches!(
+        Scene::from_json("{\"version\": 1}"),
+        Err(SceneError::Parse(_))
+    ));
+}
+
+#[test]
+fn url_params_roundtrip() {
+    let mut scene = cubic_scene();
+    scene.max_iterations = 64;
+    // beyond what f64 holds
+    scene.center = [
+        "-0.74364388703715870475219150611477".parse().unwrap(),
+        BigFloat::from_f64(0.131_825_904_205_311_97),
+    ];
+    scene.scale = 2.5e-13;
+    scene.aspect = AspectPolicy::Fill;
+    scene.method = Method::Halley;
+    scene.relaxation = [1.5, -0.5];
+    scene.no_basin_color = [0.5, 0.25, 1.0];
+    scene.rotation = 0.5;
+    scene.arbitrary_precision = true;
+    let params = scene_to_url_params(&scene);
+    let loaded = scene_from_url_params(
+        params.iter().map(|(key, value)| (*key, value.as_str())),
+        &cubic_scene(),
+    )
+    .unwrap();
+    assert_eq!(loaded, scene);
+}
+
+#[test]
+fn url_query_encoding() {
+    let params = [
+        ("roots", "1 0;-0.5 0.8660254".to_owned()),
+        ("method", "householder:3".to_owned()),
+    ];
+    assert_eq!(
+        url_query(&params),
+        "roots=1+0%3B-0.5+0.8660254&method=householder%3A3"
+    );
+    assert_eq!(url_query(&[]), "");
+}
+
+#[test]
+fn url_params_partial() {
+    let base = cubic_scene();
+    let scene = scene_from_url_params([("iter", "12"), ("unknown", "1")], &base).unwrap();
+    assert_eq!(scene.max_iterations, 12);
+    assert_eq!(scene.roots, base.roots);
+
+    assert!(matches!(
+        scene_from_url_params([("real", "1")], &base),
+        Err(SceneError::InvalidParameter("real"))
+    ));
+    // links with ranges keep working, a single range keeps the other one of the view
+    let scene = scene_from_url_params([("real", "-2 1")], &base).unwrap();
+    assert_eq!((scene.get_center(), scene.scale), (dvec2(-0.5, 0.0), 1.5));
+    assert!(matches!(
+        scene_from_url_params([("roots", "1 0")], &base),
+        Err(SceneError::Fractal(FractalError::MismatchedColors { .. }))
+    ));
+}
@@ -0,0 +1,77 @@
+use newton_fractal::{CompiledShader, FractalError, Method, ShaderCache, ShaderKey};
+use std::cell::RefCell;
+
+thread_local! {
+    static DELETED: RefCell<Vec<ShaderKey>> = RefCell::new(Vec::new());
+}
+
+// stands in for a GPU pipeline, keys without roots fail to compile
+struct FakeShader {
+    key: ShaderKey,
+    deleted: bool,
+}
+
+impl CompiledShader for FakeShader {
+    fn compile(key: ShaderKey) -> Result<Self, FractalError> {
+        if key.max_roots == 0 {
+            return Err(FractalError::Shader("no roots".to_owned()));
+        }
+        Ok(Self {
+            key,
+            deleted: false,
+        })
+    }
+
+    fn delete(&mut self) {
+        self.deleted = true;
+        DELETED.with(|deleted| deleted.borrow_mut().push(self.key));
+    }
+}
+
+fn key(method: Method) -> ShaderKey {
+    ShaderKey {
+        method,
+        ..ShaderKey::default()
+    }
+}
+
+#[test]
+fn active_shader_is_never_evicted() {
+    let mut cache: ShaderCache<FakeShader> = ShaderCache::new(3);
+    for method in [Method::Newton, Method::Halley, Method::Schroeder] {
+        cache.get(key(method)).unwrap();
+    }
+    // the oldest entry is the one being drawn with
+    cache.get(key(Method::Newton)).unwrap();
+    cache.set_capacity(1);
+    assert_eq!(cache.stats().entries, 1);
+    assert_eq!(cache.get_active_key(), Some(key(Method::Newton)));
+    assert!(!cache.get_active().unwrap().deleted);
+    DELETED.with(|deleted| {
+        assert!(!deleted.borrow().contains(&key(Method::Newton)));
+        assert_eq!(deleted.borrow().len(), 2);
+    });
+
+    // a new shader replaces it as the active one, only then can it go
+    cache.get(key(Method::Chebyshev)).unwrap();
+    assert!(!cache.contains(key(Method::Newton)));
+    assert!(cache.contains(key(Method::Chebyshev)));
+}
+
+#[test]
+fn failed_compiles_are_counted_apart() {
+    let mut cache: ShaderCache<FakeShader> = ShaderCache::new(2);
+    cache.get(key(Method::Newton)).unwrap();
+    let broken = ShaderKey {
+        max_roots: 0,
+        ..ShaderKey::default()
+    };
+    assert!(cache.get(broken).is_err());
+    assert!(cache.get(broken).is_err());
+    cache.get(key(Method::Newton)).unwrap();
+
+    let stats = cache.stats();
+    assert_eq!((stats.hits, stats.misses, stats.failures), (1, 2, 1));
+    // the previous shader stays active
+    assert_eq!(cache.get_active_key(), Some(key(Method::Newton)));
+}
@@ -0,0 +1,181 @@
+#[path = "../common/mod.rs"]
+mod common;
+
+use macroquad::math::{dvec2, vec2, DVec2};
+use newton_fractal::{AspectPolicy, BigFloat, Real, Scene, Viewport, MIN_SCALE};
+use std::f32::consts::PI;
+
+fn assert_close(a: DVec2, b: DVec2) {
+    assert!((a - b).length() < 1e-4, "{} != {}", a, b);
+}
+
+#[test]
+fn square_pixels() {
+    let viewport = Viewport::new(dvec2(0.5, -0.25), 1.0, vec2(400.0, 200.0));
+    // fit spans the shorter side, the longer one shows more of the plane
+    assert_eq!(viewport.imag_range(), vec2(-1.25, 0.75));
+    assert_eq!(viewport.real_range(), vec2(-1.5, 2.5));
+    assert_close(
+        viewport.screen_to_complex(vec2(0.0, 0.0)),
+        dvec2(-1.5, -1.25),
+    );
+    assert_close(
+        viewport.screen_to_complex(vec2(400.0, 200.0)),
+        dvec2(2.5, 0.75),
+    );
+
+    let mut fill = viewport;
+    fill.set_aspect(AspectPolicy::Fill);
+    assert_eq!(fill.real_range(), vec2(-0.5, 1.5));
+    assert_eq!(fill.imag_range(), vec2(-0.75, 0.25));
+}
+
+#[test]
+fn resize_keeps_scale() {
+    let viewport = Viewport::new(dvec2(0.0, 0.0), 1.0, vec2(200.0, 200.0));
+    let wide = viewport.with_size(vec2(400.0, 200.0));
+    assert_eq!(wide.pixel_size(), viewport.pixel_size());
+    assert_eq!(wide.imag_range(), viewport.imag_range());
+    let tall = viewport.with_size(vec2(200.0, 400.0));
+    assert_eq!(tall.real_range(), viewport.real_range());
+}
+
+#[test]
+fn from_ranges_contains_them() {
+    let viewport = Viewport::from_ranges(vec2(-2.0, 2.0), vec2(-0.5, 0.5), vec2(100.0, 100.0));
+    assert_eq!(viewport.get_center(), dvec2(0.0, 0.0));
+    assert_eq!(viewport.real_range(), vec2(-2.0, 2.0));
+    assert_eq!(viewport.imag_range(), vec2(-2.0, 2.0));
+}
+
+#[test]
+fn conversions_roundtrip() {
+    let mut viewport = Viewport::new(dvec2(0.3, 0.7), 0.5, vec2(640.0, 480.0));
+    viewport.set_rotation(0.6);
+    for position in [vec2(0.0, 0.0), vec2(123.0, 456.0), vec2(640.0, 10.0)] {
+        let z = viewport.screen_to_complex(position);
+        assert_close(
+            viewport.complex_to_screen(z).as_dvec2(),
+            position.as_dvec2(),
+        );
+    }
+    assert_close(
+        viewport.screen_to_complex(vec2(320.0, 240.0)),
+        viewport.get_center(),
+    );
+}
+
+#[test]
+fn zoom_and_drag() {
+    let mut viewport = Viewport::new(dvec2(0.0, 0.0), 1.0, vec2(300.0, 200.0));
+    viewport.set_rotation(1.0);
+    let cursor = vec2(40.0, 150.0);
+    let point = viewport.screen_to_complex(cursor);
+    viewport.zoom(cursor, 0.5);
+    assert_eq!(viewport.get_scale(), 0.5);
+    assert_close(viewport.screen_to_complex(cursor), point);
+
+    // the grabbed point follows the cursor
+    viewport.drag(vec2(25.0, -10.0));
+    assert_close(
+        viewport.screen_to_complex(cursor + vec2(25.0, -10.0)),
+        point,
+    );
+
+    // past f32 the view asks for double-float, and stops before that runs out too
+    assert!(!viewport.needs_deep_zoom());
+    for _ in 0..100 {
+        viewport.zoom(cursor + vec2(25.0, -10.0), 0.5);
+    }
+    assert!(viewport.needs_deep_zoom());
+    assert!(viewport.get_scale() > 1e-10 && viewport.get_scale() < 1e-8);
+    assert_close(viewport.screen_to_complex(cursor), point);
+}
+
+#[test]
+fn scale_stays_above_minimum() {
+    let mut viewport = Viewport::new(dvec2(4.0, -1.0), 1.0, vec2(300.0, 200.0));
+    assert_eq!(viewport.min_scale(), 4.0 * MIN_SCALE);
+    viewport.set_scale(1e-12);
+    assert_eq!(viewport.get_scale(), viewport.min_scale());
+    viewport.set_scale(0.0);
+    viewport.set_scale(f64::NAN);
+    assert_eq!(viewport.get_scale(), viewport.min_scale());
+    viewport.set_scale(0.25);
+    assert_eq!(viewport.get_scale(), 0.25);
+}
+
+#[test]
+fn precise_zoom_goes_beyond_f64() {
+    let mut viewport = Viewport::new(dvec2(1.0, 0.5), 1.0, vec2(100.0, 100.0));
+    viewport.set_arbitrary_precision(true);
+    // the zoom closes in on 1.5 + 0.5i, which stays 25 pixels right of the center
+    for _ in 0..100 {
+        viewport.zoom(vec2(75.0, 50.0), 0.5);
+    }
+    assert!(viewport.get_scale() < 1e-30);
+    assert_eq!(viewport.get_center(), dvec2(1.5, 0.5));
+    let offset = viewport.get_precise_center().re.clone() - BigFloat::from_f64(1.5);
+    let expected = -25.0 * viewport.pixel_size();
+    assert!((offset.to_f64() - expected).abs() < expected.abs() * 1e-6);
+    assert_close(
+        viewport.complex_to_screen(dvec2(1.5, 0.5)).as_dvec2(),
+        dvec2(75.0, 50.0),
+    );
+
+    // without it the view is held back to the minimum scale again
+    viewport.set_arbitrary_precision(false);
+    assert_eq!(viewport.get_scale(), viewport.min_scale());
+}
+
+#[test]
+fn deep_zoom_keeps_pixels_apart() {
+    let center = dvec2(-0.75, 0.1);
+    let mut viewport = Viewport::new(center, 1e-8, vec2(100.0, 100.0));
+    let left = viewport.screen_to_complex(vec2(0.0, 50.0));
+    let right = viewport.screen_to_complex(vec2(1.0, 50.0));
+    assert!((right.x - left.x - viewport.pixel_size()).abs() < 1e-14);
+    // f32 would have put both pixels on the same point
+    assert_eq!(left.as_vec2(), right.as_vec2());
+
+    viewport.drag(vec2(-3.0, 0.0));
+    assert!((viewport.get_center().x - center.x - 3.0 * viewport.pixel_size()).abs() < 1e-14);
+}
+
+#[test]
+fn rotate_around_point() {
+    let mut viewport = Viewport::new(dvec2(0.2, 0.1), 1.0, vec2(300.0, 200.0));
+    let cursor = vec2(250.0, 30.0);
+    let point = viewport.screen_to_complex(cursor);
+    viewport.rotate(cursor, 2.5);
+    viewport.rotate(cursor, 2.5);
+    assert_close(viewport.screen_to_complex(cursor), point);
+    // kept within -pi and pi
+    assert!((viewport.get_rotation() - (5.0 - 2.0 * PI)).abs() < 1e-5);
+}
+
+#[test]
+fn rotated_cpu_mapping() {
+    let mut viewport = Viewport::new(dvec2(-0.5, 0.25), 0.75, vec2(64.0, 32.0));
+    viewport.set_rotation(-0.8);
+    let mut renderer = common::pair_renderer(10);
+    renderer.set_viewport(&viewport);
+    for (x, y) in [(0, 0), (63, 31), (10, 20)] {
+        let z = renderer.pixel_to_complex(x, y, 64, 32);
+        let expected = viewport.screen_to_complex(vec2(x as f32 + 0.5, y as f32 + 0.5));
+        assert_close(dvec2(z.re, z.im), expected);
+    }
+
+    let mut scene = Scene::new(
+        &common::pair_roots(),
+        &common::pair_colors(),
+        10,
+        viewport.get_center(),
+        viewport.get_scale(),
+    );
+    scene.rotation = viewport.get_rotation();
+    let loaded = Scene::from_json(&scene.to_json()).unwrap();
+    let renderer = loaded.cpu_renderer(64, 32).unwrap();
+    assert_eq!(renderer.get_rotation(), -0.8);
+    assert_eq!(loaded.viewport(vec2(64.0, 32.0)), viewport);
+}